@@ -1,4 +1,12 @@
+use crate::{X509ClientResult, X509Sniff, X509Type};
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use http::HeaderMap;
 use std::fmt::{Debug, Display};
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
 
 /// X509 Deserializer API
 pub trait X509Iterator: IntoIterator + FromIterator<Self::Item>
@@ -18,3 +26,220 @@ where
 
 /// Error type bounds
 pub trait X509IteratorError: Display + Debug {}
+
+/// X509 Transport API
+pub trait X509Transport {
+    /// Transfer the payload located at `url`.
+    fn fetch(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> impl Future<Output = X509ClientResult<X509Payload>> + Send;
+
+    /// Transfer the payload located at `url`, delivering the body in chunks as transferred.
+    /// The default implementation transfers the complete payload using [`X509Transport::fetch`].
+    fn fetch_stream(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> impl Future<Output = X509ClientResult<X509StreamPayload>> + Send
+    where
+        Self: Sync,
+    {
+        async move { Ok(X509StreamPayload::Complete(self.fetch(url, options).await?)) }
+    }
+}
+
+/// Streamed transfer, see [`X509Transport::fetch_stream`]
+pub enum X509StreamPayload {
+    /// Body of a single document, in chunks as transferred. The transport applies the transfer limit.
+    Chunks(X509Hint, BoxStream<'static, X509ClientResult<Bytes>>),
+    /// Complete payload, if the transport or the payload cannot be streamed, e.g. a directory
+    Complete(X509Payload),
+}
+
+/// Transfer options
+#[derive(Clone, Debug, Default)]
+pub struct X509TransportOptions {
+    /// If Some, fail once the payload exceeds `limit` bytes
+    pub limit: Option<usize>,
+    /// If Some, request the payload only if modified, e.g. `If-None-Match` and `If-Modified-Since` http headers
+    pub validators: Option<X509Validators>,
+    /// If Some, fail if establishing the connection takes longer
+    pub connect_timeout: Option<Duration>,
+    /// If Some, fail if waiting for data takes longer
+    pub read_timeout: Option<Duration>,
+}
+
+/// Cache validators of a previously transferred payload
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct X509Validators {
+    /// `ETag` http header
+    pub etag: Option<String>,
+    /// `Last-Modified` http header
+    pub last_modified: Option<String>,
+}
+
+impl X509Validators {
+    /// True if no validator is available
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Caching directive supplied by the transport, e.g. the `Cache-Control` and `Expires` http headers
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum X509Freshness {
+    /// No directive, the configured default TTL applies
+    #[default]
+    Unspecified,
+    /// The payload must not be cached
+    NoStore,
+    /// The payload is fresh for the given duration
+    MaxAge(Duration),
+}
+
+/// Encoding hint supplied by the transport
+#[derive(Clone, PartialEq, Debug)]
+pub enum X509Hint {
+    /// No hint available
+    None,
+    /// Media type, e.g. the `Content-Type` http header
+    ContentType(String),
+    /// File path, the filename extension is used
+    Path(PathBuf),
+    /// Type known to the transport, e.g. PEM for `c_rehash` entries. Not subject to the configured mappings.
+    Type(X509Type),
+}
+
+/// Single encoded document, prior to deserialization
+#[derive(Clone, Debug)]
+pub struct X509Document {
+    /// Encoding hint
+    pub hint: X509Hint,
+    /// Raw document
+    pub bytes: Bytes,
+    /// Document location, if the payload consists of independent documents, e.g. the files of a directory
+    pub path: Option<PathBuf>,
+}
+
+impl X509Document {
+    pub fn new<T: Into<Bytes>>(hint: X509Hint, bytes: T) -> Self {
+        Self {
+            hint,
+            bytes: bytes.into(),
+            path: None,
+        }
+    }
+
+    /// Set the document location
+    pub fn with_path(mut self, path: PathBuf) -> Self {
+        self.path = Some(path);
+        self
+    }
+}
+
+/// Document which failed to transfer or deserialize, e.g. an unreadable file in a directory
+#[derive(Clone, Debug)]
+pub struct X509DocumentError {
+    /// Document location
+    pub path: PathBuf,
+    /// Error description
+    pub error: String,
+}
+
+/// Transferred payload, prior to deserialization.
+/// Usually a single document, some transports return one document per certificate (e.g. LDAP attribute values).
+#[derive(Clone, Debug, Default)]
+pub struct X509Payload {
+    /// Encoded documents
+    pub documents: Vec<X509Document>,
+    /// If true, documents failing to deserialize are reported instead of failing the transfer, e.g. the files of a directory
+    pub partial: bool,
+    /// Documents which failed to transfer
+    pub errors: Vec<X509DocumentError>,
+    /// Cache validators, used to request the payload only if modified
+    pub validators: X509Validators,
+    /// If true, the payload was not modified since the supplied validators were issued, and contains no documents
+    pub not_modified: bool,
+    /// Caching directive
+    pub freshness: X509Freshness,
+    /// Urls of the followed redirects in order, the last one being the final url. Empty if not redirected.
+    pub redirects: Vec<Url>,
+    /// Status of the final http response, None for other transports
+    pub status: Option<u16>,
+    /// Headers of the final http response, empty for other transports
+    pub headers: HeaderMap,
+}
+
+impl X509Payload {
+    /// Payload consisting of a single document
+    pub fn new<T: Into<Bytes>>(hint: X509Hint, bytes: T) -> Self {
+        Self::from(vec![X509Document::new(hint, bytes)])
+    }
+
+    /// Total length of all documents in bytes
+    pub fn len(&self) -> usize {
+        self.documents.iter().map(|d| d.bytes.len()).sum()
+    }
+
+    /// True if the payload contains no bytes
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<Vec<X509Document>> for X509Payload {
+    fn from(documents: Vec<X509Document>) -> Self {
+        Self {
+            documents,
+            ..Default::default()
+        }
+    }
+}
+
+/// Deserialization outcome of a single document
+#[derive(Clone, Debug, PartialEq)]
+pub struct X509DocumentFormat {
+    /// Document location, if the payload consists of independent documents
+    pub path: Option<PathBuf>,
+    /// Type attempted first, determined from the transport hint, e.g. the `Content-Type` http header or the filename extension,
+    /// unless sniffed with at least the configured confidence
+    pub hint: X509Type,
+    /// Type the document was deserialized as
+    pub format: X509Type,
+    /// Number of certificates deserialized from the document, certificates are returned in document order
+    pub count: usize,
+    /// Type determined from the document content, None if content sniffing is disabled or inconclusive
+    pub sniffed: Option<X509Sniff>,
+}
+
+impl X509DocumentFormat {
+    /// True if the hinted type failed, and a relaxed fallback succeeded
+    pub fn is_fallback(&self) -> bool {
+        self.hint != self.format
+    }
+}
+
+/// Deserialized certificates along with the transfer metadata
+#[derive(Debug)]
+pub struct X509Response<X> {
+    /// Deserialized certificates
+    pub certificates: X,
+    /// Final url, after redirects
+    pub url: Url,
+    /// Urls of the followed redirects in order, the last one being the final url. Empty if not redirected.
+    pub redirects: Vec<Url>,
+    /// Status of the final http response, None for other transports. `304` if revalidated.
+    pub status: Option<u16>,
+    /// Headers of the final http response, empty for other transports. Those of the `304` response if revalidated.
+    pub headers: HeaderMap,
+    /// Total payload size in bytes
+    pub size: usize,
+    /// Deserialization outcome of each document
+    pub formats: Vec<X509DocumentFormat>,
+    /// Documents which failed to load, see `X509Client::get_all_with_errors`
+    pub errors: Vec<X509DocumentError>,
+    /// Time taken to transfer and deserialize
+    pub elapsed: Duration,
+}
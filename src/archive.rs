@@ -0,0 +1,179 @@
+use crate::api::{X509Document, X509DocumentError, X509Hint};
+use crate::compression::inner_path;
+use crate::parse::X509Type;
+use crate::provided::transport::check_limit;
+use crate::{X509ArchiveConfiguration, X509ClientError, X509ClientResult, X509TypeConfiguration};
+use log::warn;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+/// Archive format of a document
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum X509Archive {
+    Zip,
+    Tar,
+}
+
+impl X509Archive {
+    /// Archive format of the magic bytes, otherwise of the filename extension
+    fn detect(document: &X509Document) -> Option<Self> {
+        let bytes = document.bytes.as_ref();
+        if bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06") {
+            return Some(Self::Zip);
+        }
+        // ustar and gnu tar
+        if bytes.get(257..262) == Some(b"ustar") {
+            return Some(Self::Tar);
+        }
+
+        let path = match &document.hint {
+            X509Hint::Path(path) => path,
+            _ => return None,
+        };
+        match path.extension()?.to_string_lossy().to_lowercase().as_str() {
+            "zip" => Some(Self::Zip),
+            "tar" => Some(Self::Tar),
+            _ => None,
+        }
+    }
+}
+
+/// Extracted archive entries
+pub(crate) struct X509ArchiveEntries {
+    pub(crate) documents: Vec<X509Document>,
+    pub(crate) errors: Vec<X509DocumentError>,
+}
+
+/// Entries of `document` if it is a zip or tar archive, None otherwise.
+/// Only certificate files of a configured extension are extracted, see [`recognise`]. Entry paths are relative to the document path, if any.
+/// Fails if the archive exceeds the configured entry count, or if the extracted files exceed the configured size or `limit`.
+pub(crate) fn extract(
+    document: &X509Document,
+    config: &X509ArchiveConfiguration,
+    types: &X509TypeConfiguration,
+    limit: Option<usize>,
+) -> X509ClientResult<Option<X509ArchiveEntries>> {
+    let archive = match X509Archive::detect(document) {
+        Some(archive) => archive,
+        None => return Ok(None),
+    };
+
+    let mut extractor = Extractor {
+        parent: document.path.clone().unwrap_or_default(),
+        types,
+        max_entries: config.max_entries,
+        max_size: limit.map_or(config.max_size, |limit| limit.min(config.max_size)),
+        count: 0,
+        size: 0,
+        entries: X509ArchiveEntries {
+            documents: vec![],
+            errors: vec![],
+        },
+    };
+
+    let bytes = document.bytes.as_ref();
+    let result = match archive {
+        X509Archive::Zip => extractor.zip(bytes),
+        X509Archive::Tar => extractor.tar(bytes),
+    };
+    match result {
+        Ok(()) => Ok(Some(extractor.entries)),
+        Err(e @ X509ClientError::LimitExceeded { .. })
+        | Err(e @ X509ClientError::EntryLimitExceeded { .. }) => Err(e),
+        Err(e) => Err(X509ClientError::Error(format!(
+            "cannot read {:?} archive: {}",
+            archive, e
+        ))),
+    }
+}
+
+struct Extractor<'a> {
+    parent: PathBuf,
+    types: &'a X509TypeConfiguration,
+    max_entries: usize,
+    max_size: usize,
+    count: usize,
+    size: usize,
+    entries: X509ArchiveEntries,
+}
+
+impl Extractor<'_> {
+    fn zip(&mut self, bytes: &[u8]) -> X509ClientResult<()> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(error)?;
+        if archive.len() > self.max_entries {
+            return Err(X509ClientError::EntryLimitExceeded {
+                limit: self.max_entries,
+            });
+        }
+
+        for index in 0..archive.len() {
+            let entry = match archive.by_index(index) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.error(PathBuf::from(format!("#{}", index)), error(e));
+                    continue;
+                }
+            };
+            let path = PathBuf::from(entry.name());
+            self.entry(path, entry.is_file(), entry)?;
+        }
+        Ok(())
+    }
+
+    fn tar(&mut self, bytes: &[u8]) -> X509ClientResult<()> {
+        let mut archive = tar::Archive::new(bytes);
+        for entry in archive.entries()? {
+            let entry = entry?;
+            let path = entry.path()?.to_path_buf();
+            let is_file = entry.header().entry_type().is_file();
+            self.entry(path, is_file, entry)?;
+        }
+        Ok(())
+    }
+
+    /// Count the entry, and extract it if a recognised certificate file
+    fn entry(&mut self, path: PathBuf, is_file: bool, reader: impl Read) -> X509ClientResult<()> {
+        self.count += 1;
+        if self.count > self.max_entries {
+            return Err(X509ClientError::EntryLimitExceeded {
+                limit: self.max_entries,
+            });
+        }
+
+        if !is_file || !recognise(&path, self.types) {
+            return Ok(());
+        }
+
+        let path = self.parent.join(path);
+        let mut data = vec![];
+        let remaining = self.max_size.saturating_sub(self.size);
+        if let Err(e) = reader.take(remaining as u64 + 1).read_to_end(&mut data) {
+            self.error(path, e.into());
+            return Ok(());
+        }
+        self.size += data.len();
+        check_limit(self.size, Some(self.max_size))?;
+
+        self.entries
+            .documents
+            .push(X509Document::new(X509Hint::Path(path.clone()), data).with_path(path));
+        Ok(())
+    }
+
+    fn error(&mut self, path: PathBuf, error: X509ClientError) {
+        warn!(target:"x509-client", "cannot extract {}: {}", path.display(), error);
+        self.entries.errors.push(X509DocumentError {
+            path,
+            error: error.to_string(),
+        });
+    }
+}
+
+/// True for entries of a configured certificate filename extension, including compressed files, e.g. `ca.pem.gz`
+fn recognise(path: &Path, types: &X509TypeConfiguration) -> bool {
+    types.extension(&inner_path(path)) != X509Type::Unknown
+}
+
+fn error(e: zip::result::ZipError) -> X509ClientError {
+    X509ClientError::Error(e.to_string())
+}
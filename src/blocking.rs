@@ -0,0 +1,124 @@
+use crate::api::{X509DocumentError, X509Iterator, X509Response, X509Transport};
+use crate::provided::transport::DefaultX509Transport;
+use crate::{
+    X509Client, X509ClientConfiguration, X509ClientError, X509ClientResult, X509RequestOptions,
+};
+use tokio::runtime::{Builder, Runtime};
+use url::Url;
+
+/// Synchronous X509 Transport and Deserialize client.
+/// Wraps an [`X509Client`] along with a single-threaded runtime, each method blocks until complete.
+/// Must not be called from within an async runtime.
+pub struct X509BlockingClient<X: X509Iterator, T: X509Transport = DefaultX509Transport> {
+    client: X509Client<X, T>,
+    runtime: Runtime,
+}
+
+impl<X: X509Iterator> X509BlockingClient<X>
+where
+    X509ClientError: From<X::X509IteratorError>,
+{
+    /// Instantiate X509 Blocking Client with supplied configuration and the [`DefaultX509Transport`]
+    pub fn new(config: X509ClientConfiguration) -> Self {
+        Self {
+            client: X509Client::new(config),
+            runtime: runtime(),
+        }
+    }
+}
+
+impl<X: X509Iterator, T: X509Transport> X509BlockingClient<X, T>
+where
+    X509ClientError: From<X::X509IteratorError>,
+{
+    /// Instantiate X509 Blocking Client with supplied configuration and transport.
+    /// The configured `http_client`, `recursive` and `redirect` options are ignored.
+    pub fn with_transport(config: X509ClientConfiguration, transport: T) -> Self {
+        Self {
+            client: X509Client::with_transport(config, transport),
+            runtime: runtime(),
+        }
+    }
+
+    /// Transfer and deserialize certificates, returning the first one or error on empty.
+    pub fn get(&self, url: &Url) -> X509ClientResult<X::Item> {
+        self.runtime.block_on(self.client.get(url))
+    }
+
+    /// Transfer and deserialize certificates, returning all. May be empty, depending on the deserialization implementation.
+    pub fn get_all(&self, url: &Url) -> X509ClientResult<X> {
+        self.runtime.block_on(self.client.get_all(url))
+    }
+
+    /// Transfer and deserialize certificates from the first url to succeed, see [`X509Client::get_any`].
+    pub fn get_any(&self, urls: &[Url]) -> X509ClientResult<X> {
+        self.runtime.block_on(self.client.get_any(urls))
+    }
+
+    /// Transfer and deserialize the certificates of many urls concurrently, see [`X509Client::get_batch`].
+    pub fn get_batch(
+        &self,
+        urls: impl IntoIterator<Item = Url>,
+    ) -> Vec<(Url, X509ClientResult<X>)> {
+        self.runtime.block_on(self.client.get_batch(urls))
+    }
+
+    /// Transfer and deserialize certificates, returning all. The supplied `options` override the client configuration.
+    pub fn get_all_with(&self, url: &Url, options: &X509RequestOptions) -> X509ClientResult<X> {
+        self.runtime
+            .block_on(self.client.get_all_with(url, options))
+    }
+
+    /// Transfer and deserialize certificates, returning all, along with the documents which failed to load.
+    pub fn get_all_with_errors(&self, url: &Url) -> X509ClientResult<(X, Vec<X509DocumentError>)> {
+        self.runtime.block_on(self.client.get_all_with_errors(url))
+    }
+
+    /// Transfer and deserialize certificates, returning all, along with the redirect chain.
+    pub fn get_all_with_redirects(&self, url: &Url) -> X509ClientResult<(X, Vec<Url>)> {
+        self.runtime
+            .block_on(self.client.get_all_with_redirects(url))
+    }
+
+    /// Transfer and deserialize certificates, returning all along with the transfer metadata.
+    pub fn get_response(
+        &self,
+        url: &Url,
+        options: &X509RequestOptions,
+    ) -> X509ClientResult<X509Response<X>> {
+        self.runtime
+            .block_on(self.client.get_response(url, options))
+    }
+
+    /// Remove the cached payload of `url`, in memory and on disk
+    pub fn invalidate(&self, url: &Url) -> X509ClientResult<()> {
+        self.runtime.block_on(self.client.invalidate(url))
+    }
+
+    /// Remove all cached payloads, in memory and on disk
+    pub fn invalidate_all(&self) -> X509ClientResult<()> {
+        self.runtime.block_on(self.client.invalidate_all())
+    }
+
+    /// Forget all remembered cache validators and payloads
+    pub fn clear_validators(&self) {
+        self.client.clear_validators()
+    }
+}
+
+impl<X: X509Iterator> Default for X509BlockingClient<X>
+where
+    X509ClientError: From<X::X509IteratorError>,
+{
+    /// Instantiate X509 Blocking Client with default configuration, see [`X509Client::default`]
+    fn default() -> Self {
+        Self::new(X509ClientConfiguration::default())
+    }
+}
+
+fn runtime() -> Runtime {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("runtime cannot be initialized")
+}
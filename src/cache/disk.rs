@@ -0,0 +1,434 @@
+use crate::api::{
+    X509Document, X509DocumentError, X509Freshness, X509Hint, X509Payload, X509Validators,
+};
+use crate::client::X509DiskCacheConfiguration;
+use crate::{X509ClientError, X509ClientResult, X509Type};
+use http::{HeaderName, HeaderValue};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs;
+use url::Url;
+
+/// First line of every cache file, identifies the format version
+const MAGIC: &str = "x509-client-cache 1";
+/// Cache file extension, other files in the cache directory are left alone
+const EXTENSION: &str = "x509cache";
+
+/// Distinguishes the temporary files written concurrently by the same process
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Payload read from the on-disk cache
+pub(crate) enum X509DiskCacheEntry {
+    /// Within its lifetime
+    Fresh(X509Payload),
+    /// Lifetime elapsed, may still be served offline or revalidated
+    Stale(X509Payload),
+}
+
+/// On-disk cache keyed by url, one file per url holding the documents and the response metadata.
+/// Evicts the least recently used files once the directory exceeds the configured capacity.
+#[derive(Clone)]
+pub(crate) struct X509DiskCache {
+    directory: PathBuf,
+    capacity: u64,
+    ttl: Duration,
+    offline: bool,
+}
+
+impl X509DiskCache {
+    pub(crate) fn new(config: &X509DiskCacheConfiguration) -> Self {
+        Self {
+            directory: config.directory.clone(),
+            capacity: config.capacity,
+            ttl: config.ttl,
+            offline: config.offline,
+        }
+    }
+
+    pub(crate) fn offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Local schemes are never cached
+    pub(crate) fn accepts(&self, url: &Url) -> bool {
+        !matches!(url.scheme(), "file" | "data")
+    }
+
+    pub(crate) async fn get(&self, url: &Url) -> X509ClientResult<Option<X509DiskCacheEntry>> {
+        if !self.check_directory().await? {
+            return Ok(None);
+        }
+
+        let path = self.path(url);
+        let src = match fs::read(&path).await {
+            Ok(src) => src,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let (cached, expires, mut payload) = decode(&src).ok_or_else(|| {
+            X509ClientError::Error(format!("invalid cache file {}", path.display()))
+        })?;
+        // distinct urls sharing a file name
+        if cached != url.as_str() {
+            return Ok(None);
+        }
+
+        touch(&path).await?;
+
+        match expires.duration_since(SystemTime::now()) {
+            Ok(remaining) if !remaining.is_zero() => {
+                payload.freshness = X509Freshness::MaxAge(remaining);
+                Ok(Some(X509DiskCacheEntry::Fresh(payload)))
+            }
+            _ => {
+                payload.freshness = X509Freshness::MaxAge(Duration::ZERO);
+                Ok(Some(X509DiskCacheEntry::Stale(payload)))
+            }
+        }
+    }
+
+    /// Store `payload` for `url`, unless its freshness directive forbids it.
+    /// Payloads larger than the capacity are not stored.
+    pub(crate) async fn insert(&self, url: &Url, payload: &X509Payload) -> X509ClientResult<()> {
+        let ttl = match payload.freshness {
+            X509Freshness::NoStore => Duration::ZERO,
+            X509Freshness::MaxAge(max_age) => max_age,
+            X509Freshness::Unspecified => self.ttl,
+        };
+
+        // far beyond any certificate lifetime, avoids overflowing the system time
+        let ttl = ttl.min(Duration::from_secs(u32::MAX as u64));
+        let src = encode(url, SystemTime::now() + ttl, payload);
+        if ttl.is_zero() || src.len() as u64 > self.capacity {
+            return self.remove(url).await;
+        }
+
+        self.create_directory().await?;
+
+        // write then rename, readers never see a partial file
+        let path = self.path(url);
+        let tmp = path.with_extension(format!(
+            "tmp-{}-{}",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&tmp, src).await?;
+        fs::rename(&tmp, &path).await?;
+
+        self.evict().await
+    }
+
+    pub(crate) async fn remove(&self, url: &Url) -> X509ClientResult<()> {
+        match fs::remove_file(self.path(url)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) async fn clear(&self) -> X509ClientResult<()> {
+        if !self.check_directory().await? {
+            return Ok(());
+        }
+        for (path, _, _) in self.files().await? {
+            fs::remove_file(path).await?;
+        }
+        Ok(())
+    }
+
+    /// Create the directory if missing, accessible to the current user only
+    async fn create_directory(&self) -> X509ClientResult<()> {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        builder.mode(0o700);
+        builder.create(&self.directory).await?;
+
+        match self.check_directory().await? {
+            true => Ok(()),
+            false => Err(X509ClientError::Error(format!(
+                "cannot create cache directory {}",
+                self.directory.display()
+            ))),
+        }
+    }
+
+    /// False if the directory does not exist, fails if it is owned by another user,
+    /// whose files could be served or who could read the cached payloads
+    async fn check_directory(&self) -> X509ClientResult<bool> {
+        let metadata = match fs::metadata(&self.directory).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            // SAFETY: geteuid has no preconditions and cannot fail
+            if metadata.uid() != unsafe { libc::geteuid() } {
+                return Err(X509ClientError::Error(format!(
+                    "cache directory {} is not owned by the current user",
+                    self.directory.display()
+                )));
+            }
+        }
+        Ok(metadata.is_dir())
+    }
+
+    /// Remove the least recently used files until the directory fits the capacity
+    async fn evict(&self) -> X509ClientResult<()> {
+        let mut files = self.files().await?;
+        let mut size: u64 = files.iter().map(|(_, len, _)| len).sum();
+
+        files.sort_by_key(|(_, _, used)| *used);
+        for (path, len, _) in files {
+            if size <= self.capacity {
+                break;
+            }
+            fs::remove_file(path).await?;
+            size -= len;
+        }
+        Ok(())
+    }
+
+    /// Cache files with their length and modification time
+    async fn files(&self) -> X509ClientResult<Vec<(PathBuf, u64, SystemTime)>> {
+        let mut files = vec![];
+        let mut read_dir = match fs::read_dir(&self.directory).await {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(files),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = read_dir.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|e| e != EXTENSION) {
+                continue;
+            }
+            let metadata = entry.metadata().await?;
+            files.push((path, metadata.len(), metadata.modified()?));
+        }
+        Ok(files)
+    }
+
+    fn path(&self, url: &Url) -> PathBuf {
+        self.directory.join(format!(
+            "{:016x}.{}",
+            fnv1a(url.as_str().as_bytes()),
+            EXTENSION
+        ))
+    }
+}
+
+/// Mark a cache file as recently used
+async fn touch(path: &Path) -> X509ClientResult<()> {
+    let file = fs::File::options().append(true).open(path).await?;
+    file.into_std().await.set_modified(SystemTime::now())?;
+    Ok(())
+}
+
+/// 64-bit FNV-1a, stable across releases unlike the std hasher
+fn fnv1a(src: &[u8]) -> u64 {
+    src.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Serialize as a text header of one record per line, terminated by an empty line, followed by the document bytes:
+/// ```text
+/// x509-client-cache 1
+/// url <url>
+/// expires <unix time in ms>
+/// etag <value>
+/// last-modified <value>
+/// partial
+/// redirect <url>
+/// status <http status>
+/// header <name> <value>
+/// document <len> none|content-type <value>|path <value>|type <name>
+/// document-path <value>
+/// error <path> <error>
+/// ```
+fn encode(url: &Url, expires: SystemTime, payload: &X509Payload) -> Vec<u8> {
+    let expires = expires
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+
+    let mut header = vec![
+        MAGIC.to_string(),
+        format!("url {}", escape(url.as_str())),
+        format!("expires {}", expires),
+    ];
+    if let Some(etag) = &payload.validators.etag {
+        header.push(format!("etag {}", escape(etag)));
+    }
+    if let Some(last_modified) = &payload.validators.last_modified {
+        header.push(format!("last-modified {}", escape(last_modified)));
+    }
+    if payload.partial {
+        header.push("partial".to_string());
+    }
+    for redirect in &payload.redirects {
+        header.push(format!("redirect {}", escape(redirect.as_str())));
+    }
+    if let Some(status) = payload.status {
+        header.push(format!("status {}", status));
+    }
+    for (name, value) in &payload.headers {
+        // headers which are not visible ascii are not stored
+        if let Ok(value) = value.to_str() {
+            header.push(format!("header {} {}", name, escape(value)));
+        }
+    }
+    for document in &payload.documents {
+        let hint = match &document.hint {
+            X509Hint::None => "none".to_string(),
+            X509Hint::ContentType(content_type) => {
+                format!("content-type {}", escape(content_type))
+            }
+            X509Hint::Path(path) => format!("path {}", escape(&path.to_string_lossy())),
+            X509Hint::Type(format) => format!("type {}", type_name(format)),
+        };
+        header.push(format!("document {} {}", document.bytes.len(), hint));
+        if let Some(path) = &document.path {
+            header.push(format!("document-path {}", escape(&path.to_string_lossy())));
+        }
+    }
+    for error in &payload.errors {
+        header.push(format!(
+            "error {} {}",
+            escape(&error.path.to_string_lossy()),
+            escape(&error.error)
+        ));
+    }
+
+    let mut src = (header.join("\n") + "\n\n").into_bytes();
+    for document in &payload.documents {
+        src.extend_from_slice(&document.bytes);
+    }
+    src
+}
+
+/// Inverse of [`encode`], returning the url, the expiry and the payload
+fn decode(src: &[u8]) -> Option<(String, SystemTime, X509Payload)> {
+    let split = src.windows(2).position(|w| w == b"\n\n")?;
+    let header = std::str::from_utf8(&src[..split]).ok()?;
+    let mut body = &src[split + 2..];
+
+    let mut lines = header.lines();
+    if lines.next()? != MAGIC {
+        return None;
+    }
+
+    let mut url = None;
+    let mut expires = UNIX_EPOCH;
+    let mut payload = X509Payload::default();
+    let mut validators = X509Validators::default();
+
+    for line in lines {
+        let (record, value) = line.split_once(' ').unwrap_or((line, ""));
+        match record {
+            "url" => url = Some(unescape(value)?),
+            "expires" => expires = UNIX_EPOCH + Duration::from_millis(value.parse().ok()?),
+            "etag" => validators.etag = Some(unescape(value)?),
+            "last-modified" => validators.last_modified = Some(unescape(value)?),
+            "partial" => payload.partial = true,
+            "redirect" => payload.redirects.push(Url::parse(&unescape(value)?).ok()?),
+            "status" => payload.status = Some(value.parse().ok()?),
+            "header" => {
+                let (name, value) = value.split_once(' ').unwrap_or((value, ""));
+                payload.headers.append(
+                    HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    HeaderValue::from_str(&unescape(value)?).ok()?,
+                );
+            }
+            "document" => {
+                let (len, hint) = value.split_once(' ')?;
+                let len: usize = len.parse().ok()?;
+                let hint = match hint.split_once(' ') {
+                    None if hint == "none" => X509Hint::None,
+                    Some(("content-type", v)) => X509Hint::ContentType(unescape(v)?),
+                    Some(("path", v)) => X509Hint::Path(PathBuf::from(unescape(v)?)),
+                    Some(("type", v)) => X509Hint::Type(type_from_name(v)?),
+                    _ => return None,
+                };
+                if body.len() < len {
+                    return None;
+                }
+                let (bytes, rest) = body.split_at(len);
+                body = rest;
+                payload
+                    .documents
+                    .push(X509Document::new(hint, bytes.to_vec()));
+            }
+            "document-path" => {
+                let path = PathBuf::from(unescape(value)?);
+                payload.documents.last_mut()?.path = Some(path);
+            }
+            "error" => {
+                let (path, error) = value.split_once(' ')?;
+                payload.errors.push(X509DocumentError {
+                    path: PathBuf::from(unescape(path)?),
+                    error: unescape(error)?,
+                });
+            }
+            _ => return None,
+        }
+    }
+
+    payload.validators = validators;
+    Some((url?, expires, payload))
+}
+
+/// Stable name of a type hint, independent of the enum debug representation
+fn type_name(format: &X509Type) -> &'static str {
+    match format {
+        X509Type::Cer => "cer",
+        X509Type::Pkcs7 => "pkcs7",
+        X509Type::Pem => "pem",
+        X509Type::Crl => "crl",
+        X509Type::Pkcs12 => "pkcs12",
+        X509Type::Unknown => "unknown",
+    }
+}
+
+fn type_from_name(name: &str) -> Option<X509Type> {
+    Some(match name {
+        "cer" => X509Type::Cer,
+        "pkcs7" => X509Type::Pkcs7,
+        "pem" => X509Type::Pem,
+        "crl" => X509Type::Crl,
+        "pkcs12" => X509Type::Pkcs12,
+        "unknown" => X509Type::Unknown,
+        _ => return None,
+    })
+}
+
+/// Percent-encode the characters delimiting records
+fn escape(src: &str) -> String {
+    let mut escaped = String::with_capacity(src.len());
+    for c in src.chars() {
+        match c {
+            '%' | ' ' | '\n' | '\r' => escaped.push_str(&format!("%{:02X}", c as u8)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(src: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(src.len());
+    let mut chars = src.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                unescaped.push(u8::from_str_radix(&hex, 16).ok()? as char);
+            }
+            c => unescaped.push(c),
+        }
+    }
+    Some(unescaped)
+}
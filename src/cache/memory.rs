@@ -0,0 +1,105 @@
+use crate::api::X509Freshness;
+use crate::client::X509CacheConfiguration;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use url::Url;
+
+struct Entry<V> {
+    value: V,
+    expires: Instant,
+    used: u64,
+}
+
+/// In-memory cache keyed by url, evicting expired entries first, then the least recently used entry
+pub(crate) struct X509Cache<V> {
+    ttl: Duration,
+    capacity: usize,
+    entries: HashMap<Url, Entry<V>>,
+    clock: u64,
+}
+
+impl<V: Clone> X509Cache<V> {
+    pub(crate) fn new(config: &X509CacheConfiguration) -> Self {
+        Self {
+            ttl: config.ttl,
+            capacity: config.capacity,
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Cache of at most `capacity` entries which only expire on eviction
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self::new(&X509CacheConfiguration {
+            // far beyond any certificate lifetime, avoids overflowing the instant
+            ttl: Duration::from_secs(u32::MAX as u64),
+            capacity,
+        })
+    }
+
+    /// Fresh value for `url`, if any
+    pub(crate) fn get(&mut self, url: &Url) -> Option<V> {
+        self.clock += 1;
+        match self.entries.get_mut(url) {
+            Some(entry) if entry.expires > Instant::now() => {
+                entry.used = self.clock;
+                Some(entry.value.clone())
+            }
+            Some(_) => {
+                self.entries.remove(url);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store `value` for `url`, unless the freshness directive forbids it or it is already expired
+    pub(crate) fn insert(&mut self, url: &Url, value: V, freshness: X509Freshness) {
+        let ttl = match freshness {
+            X509Freshness::NoStore => Duration::ZERO,
+            X509Freshness::MaxAge(max_age) => max_age,
+            X509Freshness::Unspecified => self.ttl,
+        };
+
+        let now = Instant::now();
+        let expires = match now.checked_add(ttl) {
+            Some(expires) if ttl > Duration::ZERO && self.capacity > 0 => expires,
+            _ => {
+                self.entries.remove(url);
+                return;
+            }
+        };
+
+        if !self.entries.contains_key(url) && self.entries.len() >= self.capacity {
+            self.entries.retain(|_, entry| entry.expires > now);
+        }
+        if !self.entries.contains_key(url) && self.entries.len() >= self.capacity {
+            let lru = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(url, _)| url.clone());
+            if let Some(lru) = lru {
+                self.entries.remove(&lru);
+            }
+        }
+
+        self.clock += 1;
+        self.entries.insert(
+            url.clone(),
+            Entry {
+                value,
+                expires,
+                used: self.clock,
+            },
+        );
+    }
+
+    pub(crate) fn remove(&mut self, url: &Url) {
+        self.entries.remove(url);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
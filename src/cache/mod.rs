@@ -0,0 +1,5 @@
+pub(crate) use disk::*;
+pub(crate) use memory::*;
+
+mod disk;
+mod memory;
@@ -1,13 +1,27 @@
-use crate::api::X509Iterator;
-use crate::parse::{X509Parse, X509Type};
-use crate::{X509ClientError, X509ClientResult};
-use log::debug;
-use std::path::Path;
-use tokio::fs::File;
-use tokio::io::AsyncReadExt;
+use crate::api::{
+    X509Document, X509DocumentError, X509DocumentFormat, X509Freshness, X509Hint, X509Iterator,
+    X509Payload, X509Response, X509StreamPayload, X509Transport, X509TransportOptions,
+};
+#[cfg(feature = "archive")]
+use crate::archive::extract;
+use crate::cache::{X509Cache, X509DiskCache, X509DiskCacheEntry};
+#[cfg(feature = "compression")]
+use crate::compression::decompress;
+use crate::parse::{extension_type, media_type, X509Parse, X509Type, EXTENSIONS, MEDIA_TYPES};
+use crate::provided::transport::{with_timeout, DefaultX509Transport};
+use crate::reqwest::Client;
+use crate::stream::certificates;
+use crate::{X509ClientError, X509ClientResult, X509Confidence, X509EgressPolicy};
+use futures::future::Either;
+use futures::stream::{self, FuturesUnordered, Stream, StreamExt, TryStreamExt};
+use log::{debug, warn};
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use url::Url;
-#[cfg(not(test))]
-use {crate::reqwest::Client, bytes::BytesMut};
 
 /// X509 Client Configuration
 #[derive(Clone, Default)]
@@ -17,62 +31,460 @@ pub struct X509ClientConfiguration {
     /// If false, attempt to parse from all known formats before returning error.
     pub strict: bool,
 
-    /// If true, allow `File` transport scheme.
-    /// If false, transport attempts will fail for `File` scheme.
-    pub files: bool,
+    /// Content sniffing, determining the type from the payload structure, e.g. the PEM armor label or the DER structure.
+    /// If Some, a type sniffed with at least the given confidence takes precedence over the http header or filename extension,
+    /// including in strict mode. If None, only the http header or filename extension is used.
+    pub sniff: Option<X509Confidence>,
 
-    /// Limits max transfer size in bytes. If None, apply no limit.
+    /// Media types and filename extensions determining the type, and which directory and archive entries are loaded
+    pub types: X509TypeConfiguration,
+
+    /// Url schemes permitted, checked before transfer and for every redirect.
+    /// Transfers of other schemes fail with `X509ClientError::SchemeNotPermitted`.
+    pub schemes: X509SchemePolicy,
+
+    /// Limits max transfer size in bytes, applied to all transports. If None, apply no limit.
+    /// Transfers exceeding the limit fail with `X509ClientError::LimitExceeded`.
     pub limit: Option<usize>,
 
-    /// Optional Reqwest client.
+    /// If true, `File` scheme directories are loaded recursively.
+    /// If false, only the files of the directory itself are loaded.
+    pub recursive: bool,
+
+    /// If true, remember the cache validators (`ETag`, `Last-Modified`) and payload per url.
+    /// Subsequent transfers are conditional, the remembered payload is reused if not modified. `no-store` payloads are not remembered.
+    /// As many urls as the `cache` capacity are remembered (256 if `cache` is None), the least recently used first forgotten.
+    pub revalidate: bool,
+
+    /// If Some, transferred payloads are cached in memory per url, and deserialized on each request.
+    /// If None, every request is transferred.
+    pub cache: Option<X509CacheConfiguration>,
+
+    /// If Some, transferred payloads are also cached on disk per url, surviving restarts.
+    /// If None, nothing is written to disk.
+    pub disk_cache: Option<X509DiskCacheConfiguration>,
+
+    /// If Some, transfers failing with a retryable error are attempted again, see `X509ClientError::is_retryable`.
+    /// If None, a single attempt is made.
+    pub retry: Option<X509RetryConfiguration>,
+
+    /// If Some, fail with `X509ClientError::Timeout` if establishing a connection takes longer.
+    /// For HTTP, this includes receiving the response headers.
+    pub connect_timeout: Option<Duration>,
+
+    /// If Some, fail with `X509ClientError::Timeout` if waiting for data takes longer.
+    pub read_timeout: Option<Duration>,
+
+    /// If Some, fail with `X509ClientError::Timeout` if a whole request takes longer,
+    /// including all transfer attempts and the deserialization.
+    pub timeout: Option<Duration>,
+
+    /// If Some, [`X509Client::get_any`] races the urls, starting the next url once `stagger` elapsed or the previous url failed.
+    /// If None, the urls are tried one after another.
+    pub stagger: Option<Duration>,
+
+    /// Concurrency limits of [`X509Client::get_batch`]
+    pub batch: X509BatchConfiguration,
+
+    /// Redirect restrictions, applied to every redirect followed by the [`DefaultX509Transport`]
+    pub redirect: X509RedirectConfiguration,
+
+    /// If Some, transfers to hosts, ports or addresses denied by the policy fail with `X509ClientError::EgressDenied`.
+    /// Local `File` and `data` schemes are not restricted. If None, any destination is permitted.
+    pub egress: Option<X509EgressPolicy>,
+
+    /// Decompression limits, applied to compressed payloads if the `compression` feature is enabled
+    pub compression: X509CompressionConfiguration,
+
+    /// Archive limits, applied to zip and tar payloads if the `archive` feature is enabled
+    pub archive: X509ArchiveConfiguration,
+
+    /// Optional Reqwest client, used by the [`DefaultX509Transport`].
     /// If None, a default Reqwest client will be instantiated.
-    #[cfg(not(test))]
+    ///
+    /// A configured client is used as is: the addresses it connects to are not checked against `egress`,
+    /// only the addresses of the url resolved before the transfer are. Redirects it follows itself bypass the
+    /// `redirect` restrictions, only the final url is checked. Build it with `reqwest::redirect::Policy::none()`
+    /// to leave redirects to the transport.
     pub http_client: Option<Client>,
+}
+
+/// Url scheme policy, schemes are compared case-insensitively
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum X509SchemePolicy {
+    /// Any scheme supported by the transport
+    Any,
+    /// Only the listed schemes, e.g. `https`, `ldap` or `file`
+    Allow(Vec<String>),
+    /// Any scheme supported by the transport, except the listed schemes
+    Deny(Vec<String>),
+}
 
-    #[cfg(test)]
-    pub test_client: TestClient,
+impl Default for X509SchemePolicy {
+    /// Defaults to any scheme except `file`
+    fn default() -> Self {
+        Self::Deny(vec!["file".to_string()])
+    }
 }
 
-// pub trait X509HttpClient {
-//     fn get<U: IntoUrl>(
-//         &self,
-//         url: U,
-//     ) -> X509ClientResult<impl Future<Output = Result<Response, crate::Error>>>;
-// }
+impl X509SchemePolicy {
+    /// Only `https`
+    pub fn https() -> Self {
+        Self::Allow(vec!["https".to_string()])
+    }
 
-#[cfg(test)]
-#[derive(Clone, Default)]
-pub struct TestClient {
-    pub headers: http::HeaderMap,
-    pub bytes: bytes::Bytes,
+    /// Only `http` and `https`
+    pub fn http() -> Self {
+        Self::Allow(vec!["http".to_string(), "https".to_string()])
+    }
+
+    /// True if `scheme` is permitted
+    pub fn permits(&self, scheme: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Allow(schemes) => schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme)),
+            Self::Deny(schemes) => !schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme)),
+        }
+    }
+}
+
+/// Per-request options, overriding the client configuration.
+/// Options set to None use the configured value.
+#[derive(Clone, Debug, Default)]
+pub struct X509RequestOptions {
+    /// Overrides the configured `connect_timeout`
+    pub connect_timeout: Option<Duration>,
+    /// Overrides the configured `read_timeout`
+    pub read_timeout: Option<Duration>,
+    /// Overrides the configured `timeout`
+    pub timeout: Option<Duration>,
+}
+
+/// Batch concurrency limits
+#[derive(Clone, Debug)]
+pub struct X509BatchConfiguration {
+    /// Max number of urls transferred concurrently
+    pub concurrency: usize,
+    /// Max number of urls of the same host and port transferred concurrently. Urls without host are not limited per host.
+    pub per_host: usize,
+}
+
+impl Default for X509BatchConfiguration {
+    /// Defaults are a `concurrency` of 16 and a `per_host` limit of 4
+    fn default() -> Self {
+        Self {
+            concurrency: 16,
+            per_host: 4,
+        }
+    }
+}
+
+/// Redirect restrictions. Refused redirects fail with `X509ClientError::RedirectRefused`.
+#[derive(Clone, Debug)]
+pub struct X509RedirectConfiguration {
+    /// Max number of redirects followed per transfer
+    pub max: usize,
+    /// If false, refuse redirects from `https` to `http`
+    pub allow_downgrade: bool,
+    /// If false, refuse redirects to another host
+    pub allow_cross_host: bool,
+}
+
+impl Default for X509RedirectConfiguration {
+    /// Defaults are a `max` of 10 redirects, allowing downgrades and cross-host redirects
+    fn default() -> Self {
+        Self {
+            max: 10,
+            allow_downgrade: true,
+            allow_cross_host: true,
+        }
+    }
+}
+
+/// Decompression configuration, bounding the size of decompressed payloads against compression bombs
+#[derive(Clone, Debug)]
+pub struct X509CompressionConfiguration {
+    /// Max size of a decompressed document in bytes, fails with `X509ClientError::LimitExceeded` beyond.
+    /// Applies even if no transfer `limit` is configured, the transfer `limit` also applies if lower.
+    pub max_size: usize,
+}
+
+impl Default for X509CompressionConfiguration {
+    /// Defaults to a `max_size` of 16 MiB
+    fn default() -> Self {
+        Self {
+            max_size: 16 * 1024 * 1024,
+        }
+    }
+}
+
+/// Archive configuration.
+/// Every recognised certificate file of a zip or tar archive is deserialized, the entry path is recorded in the response formats.
+/// Entries failing to load are reported as document errors.
+#[derive(Clone, Debug)]
+pub struct X509ArchiveConfiguration {
+    /// Max number of entries, including directories and unrecognised files.
+    /// Archives of more entries fail with `X509ClientError::EntryLimitExceeded`.
+    pub max_entries: usize,
+    /// Max total size of the extracted certificate files in bytes, fails with `X509ClientError::LimitExceeded` beyond.
+    /// The transfer `limit` also applies, if lower.
+    pub max_size: usize,
+}
+
+impl Default for X509ArchiveConfiguration {
+    /// Defaults are a `max_entries` of 1024 and a `max_size` of 16 MiB
+    fn default() -> Self {
+        Self {
+            max_entries: 1024,
+            max_size: 16 * 1024 * 1024,
+        }
+    }
+}
+
+/// Type mapping of the `Content-Type` http header, the `data` scheme media type and filename extensions.
+/// Keys are lowercase: media types without parameters, e.g. `application/pkix-cert`, and extensions without the dot, e.g. `cer`.
+/// Entries may be added, overridden or removed, unmapped media types and extensions are `X509Type::Unknown`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct X509TypeConfiguration {
+    /// Type per media type. PKCS7 media types of an `smime-type` other than `certs-only` or `signed-data` are `X509Type::Unknown`.
+    pub media_types: HashMap<String, X509Type>,
+    /// Type per filename extension. Directory and archive entries are only loaded if their extension is mapped.
+    pub extensions: HashMap<String, X509Type>,
+}
+
+impl Default for X509TypeConfiguration {
+    /// Defaults to the registered and de-facto certificate media types, and the `cer`, `p7c` and `pem` extensions
+    fn default() -> Self {
+        let table = |table: &[(&str, X509Type)]| {
+            table
+                .iter()
+                .map(|(key, x509_type)| (key.to_string(), x509_type.clone()))
+                .collect()
+        };
+        Self {
+            media_types: table(&MEDIA_TYPES),
+            extensions: table(&EXTENSIONS),
+        }
+    }
+}
+
+impl X509TypeConfiguration {
+    /// Type of the hint
+    pub fn hint(&self, hint: &X509Hint) -> X509Type {
+        match hint {
+            X509Hint::None => X509Type::Unknown,
+            X509Hint::ContentType(content_type) => self.media_type(content_type),
+            X509Hint::Path(path) => self.extension(path),
+            X509Hint::Type(format) => format.clone(),
+        }
+    }
+
+    /// Type of the media type, ignoring parameters other than `smime-type`
+    pub fn media_type(&self, content_type: &str) -> X509Type {
+        media_type(content_type, |essence| {
+            self.media_types.get(essence).cloned()
+        })
+    }
+
+    /// Type of the filename extension of `path`
+    pub fn extension(&self, path: &Path) -> X509Type {
+        extension_type(path, |extension| self.extensions.get(extension).cloned())
+    }
+}
+
+/// In-memory cache configuration.
+/// Entry lifetime is determined by the `Cache-Control` (`max-age`, `no-store`, `no-cache`) and `Expires` http headers, if present.
+#[derive(Clone, Debug)]
+pub struct X509CacheConfiguration {
+    /// Lifetime of entries without a caching directive, e.g. `File` scheme payloads
+    pub ttl: Duration,
+    /// Max number of entries, the least recently used entry is evicted first
+    pub capacity: usize,
+}
+
+impl Default for X509CacheConfiguration {
+    /// Defaults are a `ttl` of 5 minutes and a `capacity` of 256 entries
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(300),
+            capacity: 256,
+        }
+    }
+}
+
+/// Where a payload came from, determines the caches it is stored in
+#[derive(Clone, Copy, PartialEq)]
+enum Source {
+    Memory,
+    Disk,
+    Transfer,
 }
 
 /// X509 Transport and Deserialize client
 #[derive(Clone)]
-pub struct X509Client<X: X509Iterator> {
+pub struct X509Client<X: X509Iterator, T: X509Transport = DefaultX509Transport> {
     parser: X509Parse<X>,
-    #[cfg(not(test))]
-    http_client: Client,
-    #[cfg(test)]
-    test_client: TestClient,
-    files: bool,
+    types: X509TypeConfiguration,
+    transport: T,
+    schemes: X509SchemePolicy,
     limit: Option<usize>,
+    revalidation: Option<Arc<Mutex<X509Cache<X509Payload>>>>,
+    cache: Option<Arc<Mutex<X509Cache<X509Payload>>>>,
+    disk_cache: Option<X509DiskCache>,
+    retry: Option<X509RetryConfiguration>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    stagger: Option<Duration>,
+    batch: X509BatchConfiguration,
+    egress: Option<X509EgressPolicy>,
+    #[cfg(feature = "compression")]
+    compression: X509CompressionConfiguration,
+    #[cfg(feature = "archive")]
+    archive: X509ArchiveConfiguration,
+}
+
+/// On-disk cache configuration.
+/// Stores the documents and the response metadata of a transfer, one file per url. `File` and `data` scheme payloads are not cached.
+/// Entry lifetime is determined as for the in-memory cache, see [`X509CacheConfiguration`].
+#[derive(Clone, Debug)]
+pub struct X509DiskCacheConfiguration {
+    /// Cache directory, created on first write with permissions restricted to the current user.
+    /// On Unix, a directory owned by another user is refused.
+    pub directory: PathBuf,
+    /// Max total size of the cache files in bytes, the least recently used files are evicted first
+    pub capacity: u64,
+    /// Lifetime of entries without a caching directive
+    pub ttl: Duration,
+    /// If true, never transfer. Payloads are served from the cache regardless of their age,
+    /// urls missing from the cache fail with `X509ClientError::Offline`.
+    pub offline: bool,
+}
+
+impl Default for X509DiskCacheConfiguration {
+    /// Defaults are the `x509-client` directory of the per-user cache directory,
+    /// a `capacity` of 64 MiB, a `ttl` of 24 hours and `offline` disabled.
+    /// The per-user cache directory is `$XDG_CACHE_HOME` or `$HOME/.cache` on Unix, `%LOCALAPPDATA%` on Windows,
+    /// and the system temporary directory if neither is set.
+    fn default() -> Self {
+        Self {
+            directory: user_cache_dir().join("x509-client"),
+            capacity: 64 * 1024 * 1024,
+            ttl: Duration::from_secs(24 * 60 * 60),
+            offline: false,
+        }
+    }
+}
+
+fn user_cache_dir() -> PathBuf {
+    let var = |name| {
+        std::env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    let dir = if cfg!(windows) {
+        var("LOCALAPPDATA")
+    } else {
+        var("XDG_CACHE_HOME").or_else(|| var("HOME").map(|home| home.join(".cache")))
+    };
+    dir.unwrap_or_else(std::env::temp_dir)
+}
+
+/// Retry policy, using exponential backoff between attempts
+#[derive(Clone, Debug)]
+pub struct X509RetryConfiguration {
+    /// Max number of attempts, including the first one
+    pub attempts: usize,
+    /// Delay before the second attempt, doubled for each further attempt
+    pub backoff: Duration,
+    /// Max delay between attempts. A `Retry-After` http header exceeding this delay fails immediately.
+    pub max_backoff: Duration,
+    /// If true, each delay is randomized between half and all of its value
+    pub jitter: bool,
+}
+
+impl Default for X509RetryConfiguration {
+    /// Defaults are 3 `attempts`, a `backoff` of 200 ms, a `max_backoff` of 10 seconds and `jitter` enabled
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+        }
+    }
+}
+
+impl X509RetryConfiguration {
+    /// Delay before attempt `attempt + 1`, None if no further attempt should be made
+    fn delay(&self, attempt: usize, error: &X509ClientError) -> Option<Duration> {
+        if attempt >= self.attempts || !error.is_retryable() {
+            return None;
+        }
+
+        if let Some(retry_after) = error.retry_after() {
+            return (retry_after <= self.max_backoff).then_some(retry_after);
+        }
+
+        let exponent = (attempt - 1).min(31) as u32;
+        let delay = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(self.max_backoff);
+        if !self.jitter {
+            return Some(delay);
+        }
+
+        // random per call, avoids a dependency on a random number generator
+        let random = RandomState::new().build_hasher().finish();
+        Some(delay.mul_f64(0.5 + (random as f64 / u64::MAX as f64) / 2.0))
+    }
 }
 
 impl<X: X509Iterator> X509Client<X>
 where
     X509ClientError: From<X::X509IteratorError>,
 {
-    /// Instantiate X509 Client with supplied configuration
+    /// Instantiate X509 Client with supplied configuration and the [`DefaultX509Transport`]
     pub fn new(config: X509ClientConfiguration) -> Self {
+        let transport = DefaultX509Transport::new(&config);
+        Self::with_transport(config, transport)
+    }
+}
+
+impl<X: X509Iterator, T: X509Transport> X509Client<X, T>
+where
+    X509ClientError: From<X::X509IteratorError>,
+{
+    /// Instantiate X509 Client with supplied configuration and transport.
+    /// The configured `http_client`, `recursive` and `redirect` options are ignored.
+    pub fn with_transport(config: X509ClientConfiguration, transport: T) -> Self {
         X509Client {
-            parser: X509Parse::new(config.strict),
-            #[cfg(not(test))]
-            http_client: config.http_client.unwrap_or_default(),
-            #[cfg(test)]
-            test_client: config.test_client,
-            files: config.files,
+            parser: X509Parse::new(config.strict).with_sniff(config.sniff),
+            types: config.types,
+            transport,
+            schemes: config.schemes,
             limit: config.limit,
+            revalidation: config.revalidate.then(|| {
+                let capacity = config.cache.clone().unwrap_or_default().capacity;
+                Arc::new(Mutex::new(X509Cache::with_capacity(capacity)))
+            }),
+            cache: config
+                .cache
+                .as_ref()
+                .map(|cache| Arc::new(Mutex::new(X509Cache::new(cache)))),
+            disk_cache: config.disk_cache.as_ref().map(X509DiskCache::new),
+            retry: config.retry,
+            connect_timeout: config.connect_timeout,
+            read_timeout: config.read_timeout,
+            timeout: config.timeout,
+            stagger: config.stagger,
+            batch: config.batch,
+            egress: config.egress,
+            #[cfg(feature = "compression")]
+            compression: config.compression,
+            #[cfg(feature = "archive")]
+            archive: config.archive,
         }
     }
 
@@ -87,92 +499,657 @@ where
 
     /// Transfer and deserialize certificates, returning all. May be empty, depending on the deserialization implementation.
     pub async fn get_all(&self, url: &Url) -> X509ClientResult<X> {
-        debug!(target:"x509-client", "attempting certificate(s) download: {}", url);
+        Ok(self.get_all_with_errors(url).await?.0)
+    }
+
+    /// Transfer and deserialize certificates from the first url to succeed, e.g. mirrors of the same issuer certificate.
+    /// Urls are tried in order, or raced if `stagger` is configured.
+    /// Fails with `X509ClientError::AllFailed` if every url fails.
+    pub async fn get_any(&self, urls: &[Url]) -> X509ClientResult<X> {
+        if urls.is_empty() {
+            return Err(X509ClientError::Error("no urls supplied".to_string()));
+        }
 
-        match url.scheme() {
-            "file" => {
-                if !self.files {
-                    return Err(X509ClientError::Error(
-                        "file scheme not permitted".to_string(),
-                    ));
+        let mut failures = match self.stagger {
+            None => {
+                let mut failures = vec![];
+                for (index, url) in urls.iter().enumerate() {
+                    match self.get_all(url).await {
+                        Ok(certificates) => return Ok(certificates),
+                        Err(e) => failures.push((index, e)),
+                    }
                 }
-                let path = url.to_file_path().map_err(|_| {
-                    X509ClientError::Error(format!("cannot parse file url {}", url))
-                })?;
-                self.file_read_certificates(path.as_path()).await
+                failures
             }
-            _ => self.http_get_certificates(url).await,
-        }
-    }
-
-    async fn file_read_certificates(&self, path: &Path) -> X509ClientResult<X> {
-        let mut file = File::open(path).await?;
-        let mut data = vec![];
-        file.read_to_end(&mut data).await?;
-        self.parser.parse(&path.into(), data)
-    }
-
-    async fn http_get_certificates(&self, origin_url: &Url) -> X509ClientResult<X> {
-        #[cfg(not(test))]
-        let (headers, bytes) = {
-            let mut resp = self
-                .http_client
-                .get(origin_url.as_str())
-                .send()
-                .await?
-                .error_for_status()?;
-
-            let x509_type = X509Type::from(resp.headers());
-
-            let buf = match self.limit {
-                None => resp.bytes().await?,
-                Some(limit) => {
-                    let mut buf = BytesMut::new();
-                    while let Some(b) = resp.chunk().await? {
-                        buf.extend(b);
-                        if buf.len() > limit {
-                            return Err(X509ClientError::Error(
-                                format!(
-                                    "total transferred bytes {} exceeded limit {}",
-                                    buf.len(),
-                                    limit
-                                )
-                                .to_string(),
-                            ));
+            Some(stagger) => match self.race(urls, stagger).await {
+                Ok(certificates) => return Ok(certificates),
+                Err(failures) => failures,
+            },
+        };
+
+        failures.sort_by_key(|(index, _)| *index);
+        Err(X509ClientError::AllFailed(
+            failures
+                .into_iter()
+                .map(|(index, e)| (urls[index].clone(), e))
+                .collect(),
+        ))
+    }
+
+    /// Start the next url every `stagger`, or as soon as a running url fails. The first success cancels the others.
+    async fn race(
+        &self,
+        urls: &[Url],
+        stagger: Duration,
+    ) -> Result<X, Vec<(usize, X509ClientError)>> {
+        let start = |index: usize| async move { (index, self.get_all(&urls[index]).await) };
+
+        let mut failures = vec![];
+        let mut running = FuturesUnordered::new();
+        running.push(start(0));
+        let mut next = 1;
+
+        while !running.is_empty() {
+            tokio::select! {
+                Some((index, result)) = running.next() => match result {
+                    Ok(certificates) => {
+                        debug!(target:"x509-client", "first success: {}", urls[index]);
+                        return Ok(certificates);
+                    }
+                    Err(e) => {
+                        failures.push((index, e));
+                        if next < urls.len() {
+                            running.push(start(next));
+                            next += 1;
                         }
                     }
-                    buf.into()
+                },
+                _ = tokio::time::sleep(stagger), if next < urls.len() => {
+                    running.push(start(next));
+                    next += 1;
+                }
+            }
+        }
+        Err(failures)
+    }
+
+    /// Transfer and deserialize the certificates of many urls concurrently, within the configured `batch` limits.
+    /// Returns the result of each url in the supplied order, a failing url does not fail the batch.
+    pub async fn get_batch(
+        &self,
+        urls: impl IntoIterator<Item = Url>,
+    ) -> Vec<(Url, X509ClientResult<X>)> {
+        let mut results = self.batch(urls).collect::<Vec<_>>().await;
+        results.sort_by_key(|(index, _, _)| *index);
+        results
+            .into_iter()
+            .map(|(_, url, result)| (url, result))
+            .collect()
+    }
+
+    /// Transfer and deserialize the certificates of many urls concurrently, within the configured `batch` limits.
+    /// Yields the result of each url once complete, a failing url does not fail the batch.
+    pub fn get_batch_stream(
+        &self,
+        urls: impl IntoIterator<Item = Url>,
+    ) -> impl Stream<Item = (Url, X509ClientResult<X>)> + '_ {
+        self.batch(urls).map(|(_, url, result)| (url, result))
+    }
+
+    /// Yields the supplied index, the url and the result of each url once complete
+    fn batch(
+        &self,
+        urls: impl IntoIterator<Item = Url>,
+    ) -> impl Stream<Item = (usize, Url, X509ClientResult<X>)> + '_ {
+        let pending = urls.into_iter().enumerate().collect::<VecDeque<_>>();
+        let running = FuturesUnordered::new();
+        let hosts: HashMap<String, usize> = HashMap::new();
+
+        stream::unfold(
+            (pending, running, hosts),
+            move |(mut pending, mut running, mut hosts)| async move {
+                // start pending urls within the limits, skipping urls of busy hosts
+                let mut next = 0;
+                while running.len() < self.batch.concurrency.max(1) && next < pending.len() {
+                    let host = batch_host(&pending[next].1);
+                    let count = host.as_ref().and_then(|h| hosts.get(h)).copied();
+                    if count.unwrap_or_default() >= self.batch.per_host.max(1) {
+                        next += 1;
+                        continue;
+                    }
+                    if let Some(host) = host {
+                        *hosts.entry(host).or_default() += 1;
+                    }
+                    let (index, url) = pending.remove(next)?;
+                    running.push(async move {
+                        let result = self.get_all(&url).await;
+                        (index, url, result)
+                    });
+                }
+
+                let (index, url, result) = running.next().await?;
+                if let Some(count) = batch_host(&url).and_then(|h| hosts.get_mut(&h)) {
+                    *count -= 1;
+                }
+                Some(((index, url, result), (pending, running, hosts)))
+            },
+        )
+    }
+
+    /// Transfer and deserialize certificates, returning all. The supplied `options` override the client configuration.
+    pub async fn get_all_with(
+        &self,
+        url: &Url,
+        options: &X509RequestOptions,
+    ) -> X509ClientResult<X> {
+        Ok(self.load(url, options).await?.certificates)
+    }
+
+    /// Transfer and deserialize certificates, returning all, along with the documents which failed to load.
+    /// Only payloads consisting of independent documents, e.g. directories, report errors per document.
+    /// Otherwise, any error fails the transfer.
+    pub async fn get_all_with_errors(
+        &self,
+        url: &Url,
+    ) -> X509ClientResult<(X, Vec<X509DocumentError>)> {
+        let response = self.load(url, &X509RequestOptions::default()).await?;
+        Ok((response.certificates, response.errors))
+    }
+
+    /// Transfer and deserialize certificates, returning all, along with the redirect chain:
+    /// every url requested in order, starting with `url`, the last one being the final url.
+    pub async fn get_all_with_redirects(&self, url: &Url) -> X509ClientResult<(X, Vec<Url>)> {
+        let response = self.load(url, &X509RequestOptions::default()).await?;
+        let chain = std::iter::once(url.clone())
+            .chain(response.redirects)
+            .collect();
+        Ok((response.certificates, chain))
+    }
+
+    /// Transfer and deserialize certificates, returning all along with the transfer metadata:
+    /// the final url, the http status and headers, the payload size, the detected formats and the time taken.
+    /// The supplied `options` override the client configuration.
+    pub async fn get_response(
+        &self,
+        url: &Url,
+        options: &X509RequestOptions,
+    ) -> X509ClientResult<X509Response<X>> {
+        self.load(url, options).await
+    }
+
+    /// Transfer and deserialize certificates, yielding each certificate as soon as deserialized.
+    /// PEM documents are deserialized block by block as transferred, other encodings once the transfer is complete.
+    /// The transfer `limit` and the connect and read timeouts apply, the caches, revalidation, retries and the overall `timeout` do not.
+    /// An error ends the stream.
+    pub fn get_stream(&self, url: &Url) -> impl Stream<Item = X509ClientResult<X::Item>> + '_
+    where
+        T: Sync,
+    {
+        let url = url.clone();
+        stream::once(async move { self.stream_within(&url).await }).try_flatten()
+    }
+
+    async fn stream_within<'a>(
+        &'a self,
+        url: &Url,
+    ) -> X509ClientResult<impl Stream<Item = X509ClientResult<X::Item>> + 'a>
+    where
+        T: Sync,
+    {
+        if !self.schemes.permits(url.scheme()) {
+            return Err(X509ClientError::SchemeNotPermitted(
+                url.scheme().to_string(),
+            ));
+        }
+        if let Some(egress) = &self.egress {
+            if !matches!(url.scheme(), "file" | "data") {
+                egress.check(url).await?;
+            }
+        }
+
+        debug!(target:"x509-client", "attempting certificate(s) stream: {}", url);
+
+        let options = X509TransportOptions {
+            limit: self.limit,
+            connect_timeout: self.connect_timeout,
+            read_timeout: self.read_timeout,
+            ..Default::default()
+        };
+
+        Ok(match self.transport.fetch_stream(url, &options).await? {
+            X509StreamPayload::Chunks(hint, chunks) => {
+                let url = url.clone();
+                Either::Left(certificates(
+                    self.parser.strict(),
+                    self.parser.sniff(),
+                    self.types.hint(&hint),
+                    hint,
+                    chunks,
+                    move |document| {
+                        let payload = X509Payload::from(vec![document]);
+                        Ok(self.deserialize(&url, payload)?.certificates)
+                    },
+                ))
+            }
+            X509StreamPayload::Complete(payload) => {
+                let certificates = self.deserialize(url, payload)?.certificates;
+                Either::Right(stream::iter(certificates.into_iter().map(Ok)))
+            }
+        })
+    }
+
+    /// Transfer and deserialize within the deadline.
+    /// Deserialization cannot be interrupted, the deadline is checked once complete.
+    async fn load(
+        &self,
+        url: &Url,
+        options: &X509RequestOptions,
+    ) -> X509ClientResult<X509Response<X>> {
+        let transport_options = X509TransportOptions {
+            limit: self.limit,
+            connect_timeout: options.connect_timeout.or(self.connect_timeout),
+            read_timeout: options.read_timeout.or(self.read_timeout),
+            ..Default::default()
+        };
+
+        let timeout = options.timeout.or(self.timeout);
+        let started = Instant::now();
+        let result = with_timeout(timeout, self.load_within(url, &transport_options)).await;
+
+        match timeout {
+            Some(timeout) if started.elapsed() > timeout => Err(X509ClientError::Timeout(timeout)),
+            _ => result.map(|response| X509Response {
+                elapsed: started.elapsed(),
+                ..response
+            }),
+        }
+    }
+
+    async fn load_within(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509Response<X>> {
+        if !self.schemes.permits(url.scheme()) {
+            return Err(X509ClientError::SchemeNotPermitted(
+                url.scheme().to_string(),
+            ));
+        }
+
+        debug!(target:"x509-client", "attempting certificate(s) download: {}", url);
+
+        let (payload, source) = self.fetch(url, options).await?;
+        if source == Source::Memory {
+            return self.deserialize(url, payload);
+        }
+
+        // only payloads which deserialize are cached
+        let response = self.deserialize(url, payload.clone())?;
+        self.store(url, &payload, source).await;
+        Ok(response)
+    }
+
+    /// Deserialize the documents of `payload`
+    fn deserialize(
+        &self,
+        url: &Url,
+        mut payload: X509Payload,
+    ) -> X509ClientResult<X509Response<X>> {
+        let size = payload.len();
+        let documents = std::mem::take(&mut payload.documents);
+        #[cfg(feature = "archive")]
+        let documents = self.extract(documents, &mut payload)?;
+
+        if documents.len() == 1 && !payload.partial {
+            let document = documents.into_iter().next().unwrap();
+            let (certificates, format) = self.parse(document)?;
+            return Ok(response(url, certificates, size, vec![format], payload));
+        }
+
+        let mut certificates = vec![];
+        let mut formats = vec![];
+        for document in documents {
+            let path = document.path.clone();
+            match self.parse(document) {
+                Ok((c, format)) => {
+                    certificates.extend(c);
+                    formats.push(format);
+                }
+                Err(e) if payload.partial => {
+                    let path = path.unwrap_or_default();
+                    warn!(target:"x509-client", "cannot parse {}: {}", path.display(), e);
+                    payload.errors.push(X509DocumentError {
+                        path,
+                        error: e.to_string(),
+                    });
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(response(
+            url,
+            X::from_iter(certificates),
+            size,
+            formats,
+            payload,
+        ))
+    }
+
+    /// Replace archives with their extracted entries, deserialized as independent documents.
+    /// Archives and their compressed entries are decompressed within the archive `max_size`, or the transfer `limit` if lower.
+    #[cfg(feature = "archive")]
+    fn extract(
+        &self,
+        documents: Vec<X509Document>,
+        payload: &mut X509Payload,
+    ) -> X509ClientResult<Vec<X509Document>> {
+        let limit = self.decompression_limit().min(self.archive.max_size);
+
+        let mut extracted = vec![];
+        for document in documents {
+            let decompressed = match decompress(document.clone(), limit) {
+                Ok(decompressed) => decompressed,
+                Err(e @ X509ClientError::LimitExceeded { .. }) => return Err(e),
+                // reported once deserialized
+                Err(_) if payload.partial => {
+                    extracted.push(document);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            let entries = match extract(&decompressed, &self.archive, &self.types, self.limit)? {
+                Some(entries) => entries,
+                None => {
+                    extracted.push(decompressed);
+                    continue;
                 }
             };
+            payload.partial = true;
+            payload.errors.extend(entries.errors);
 
-            (x509_type, buf)
+            // total expanded size of the entries, including compressed entries, e.g. `ca.pem.gz`
+            let mut size = 0;
+            for entry in entries.documents {
+                let path = entry.path.clone().unwrap_or_default();
+                match decompress(entry, limit - size) {
+                    Ok(entry) => {
+                        size += entry.bytes.len();
+                        extracted.push(entry);
+                    }
+                    Err(X509ClientError::LimitExceeded { size: read, .. }) => {
+                        return Err(X509ClientError::LimitExceeded {
+                            size: size + read,
+                            limit,
+                        })
+                    }
+                    Err(e) => {
+                        warn!(target:"x509-client", "cannot extract {}: {}", path.display(), e);
+                        payload.errors.push(X509DocumentError {
+                            path,
+                            error: e.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(extracted)
+    }
+
+    /// Max decompressed size of a document: the configured `max_size`, or the transfer `limit` if lower
+    #[cfg(feature = "compression")]
+    fn decompression_limit(&self) -> usize {
+        let max_size = self.compression.max_size;
+        self.limit.map_or(max_size, |limit| limit.min(max_size))
+    }
+
+    /// Decompress if compressed, then deserialize a single document
+    fn parse(&self, document: X509Document) -> X509ClientResult<(X, X509DocumentFormat)> {
+        #[cfg(feature = "compression")]
+        let document = decompress(document, self.decompression_limit())?;
+
+        let (hint, sniffed) = self
+            .parser
+            .hint(self.types.hint(&document.hint), &document.bytes);
+        let (certificates, format) = self.parser.parse(&hint, document.bytes)?;
+        let certificates = certificates.into_iter().collect::<Vec<_>>();
+        let count = certificates.len();
+        Ok((
+            X::from_iter(certificates),
+            X509DocumentFormat {
+                path: document.path,
+                hint,
+                format,
+                count,
+                sniffed,
+            },
+        ))
+    }
+
+    /// Remove the cached payload of `url`, in memory and on disk
+    pub async fn invalidate(&self, url: &Url) -> X509ClientResult<()> {
+        if let Some(cache) = &self.cache {
+            cache.lock().unwrap().remove(url);
+        }
+        if let Some(disk_cache) = &self.disk_cache {
+            disk_cache.remove(url).await?;
+        }
+        Ok(())
+    }
+
+    /// Remove all cached payloads, in memory and on disk
+    pub async fn invalidate_all(&self) -> X509ClientResult<()> {
+        if let Some(cache) = &self.cache {
+            cache.lock().unwrap().clear();
+        }
+        if let Some(disk_cache) = &self.disk_cache {
+            disk_cache.clear().await?;
+        }
+        Ok(())
+    }
+
+    /// Forget all remembered cache validators and payloads
+    pub fn clear_validators(&self) {
+        if let Some(revalidation) = &self.revalidation {
+            revalidation.lock().unwrap().clear();
+        }
+    }
+
+    /// Payload of `url` from the caches, otherwise transferred, along with where it came from
+    async fn fetch(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<(X509Payload, Source)> {
+        if let Some(cache) = &self.cache {
+            if let Some(payload) = cache.lock().unwrap().get(url) {
+                debug!(target:"x509-client", "cached payload: {}", url);
+                return Ok((payload, Source::Memory));
+            }
+        }
+
+        match &self.disk_cache {
+            Some(disk_cache) if disk_cache.accepts(url) => {
+                self.fetch_disk_cache(disk_cache, url, options).await
+            }
+            _ => Ok((self.transfer(url, options, None).await?, Source::Transfer)),
+        }
+    }
+
+    /// Serve fresh payloads from disk, otherwise transfer
+    async fn fetch_disk_cache(
+        &self,
+        disk_cache: &X509DiskCache,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<(X509Payload, Source)> {
+        let stale = match disk_cache.get(url).await {
+            Ok(Some(X509DiskCacheEntry::Fresh(payload))) => {
+                debug!(target:"x509-client", "disk cached payload: {}", url);
+                return Ok((payload, Source::Disk));
+            }
+            Ok(Some(X509DiskCacheEntry::Stale(payload))) => Some(payload),
+            Ok(None) => None,
+            Err(e) => {
+                warn!(target:"x509-client", "cannot read disk cache {}: {}", url, e);
+                None
+            }
         };
 
-        #[cfg(test)]
-        let (headers, bytes) = {
-            let _ = origin_url;
-            let _ = self.limit;
-            (
-                X509Type::from(&self.test_client.headers),
-                &self.test_client.bytes,
-            )
+        if disk_cache.offline() {
+            return match stale {
+                Some(payload) => Ok((payload, Source::Disk)),
+                None => Err(X509ClientError::Offline(url.to_string())),
+            };
+        }
+
+        Ok((self.transfer(url, options, stale).await?, Source::Transfer))
+    }
+
+    /// Cache a deserialized payload in memory, and on disk if transferred.
+    /// Disk cache failures are logged, never failing the request.
+    async fn store(&self, url: &Url, payload: &X509Payload, source: Source) {
+        if let Some(cache) = &self.cache {
+            cache
+                .lock()
+                .unwrap()
+                .insert(url, payload.clone(), payload.freshness);
+        }
+
+        match &self.disk_cache {
+            Some(disk_cache) if source == Source::Transfer && disk_cache.accepts(url) => {
+                if let Err(e) = disk_cache.insert(url, payload).await {
+                    warn!(target:"x509-client", "cannot write disk cache {}: {}", url, e);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Transfer, conditionally if revalidation is enabled and a previous payload is known.
+    /// `stale` is the expired disk cache payload, if any.
+    async fn transfer(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+        stale: Option<X509Payload>,
+    ) -> X509ClientResult<X509Payload> {
+        let revalidation = match &self.revalidation {
+            None => return self.transport_fetch(url, options).await,
+            Some(revalidation) => revalidation,
         };
 
-        self.parser.parse(&headers, bytes)
+        let previous = revalidation.lock().unwrap().get(url).or(stale);
+        let options = X509TransportOptions {
+            validators: previous.as_ref().map(|p| p.validators.clone()),
+            ..options.clone()
+        };
+
+        let payload = self.transport_fetch(url, &options).await?;
+
+        if payload.not_modified {
+            debug!(target:"x509-client", "not modified, reusing payload: {}", url);
+            let previous = previous.ok_or_else(|| {
+                X509ClientError::Error(format!("not modified without previous payload {}", url))
+            })?;
+            return Ok(X509Payload {
+                freshness: payload.freshness,
+                redirects: payload.redirects,
+                status: payload.status,
+                headers: payload.headers,
+                ..previous
+            });
+        }
+
+        // a no-store payload is not kept, not even for revalidation
+        if payload.validators.is_empty() || matches!(payload.freshness, X509Freshness::NoStore) {
+            revalidation.lock().unwrap().remove(url);
+        } else {
+            revalidation
+                .lock()
+                .unwrap()
+                .insert(url, payload.clone(), X509Freshness::Unspecified);
+        }
+
+        Ok(payload)
+    }
+
+    /// Transfer using the transport, applying the egress and retry policies
+    async fn transport_fetch(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509Payload> {
+        if let Some(egress) = &self.egress {
+            if !matches!(url.scheme(), "file" | "data") {
+                egress.check(url).await?;
+            }
+        }
+
+        let mut attempt = 1;
+        loop {
+            let error = match self.transport.fetch(url, options).await {
+                Ok(payload) => return Ok(payload),
+                Err(e) => e,
+            };
+
+            let delay = match self.retry.as_ref().and_then(|r| r.delay(attempt, &error)) {
+                Some(delay) => delay,
+                None => return Err(error),
+            };
+
+            warn!(target:"x509-client", "attempt {} failed, retrying in {:?}: {}: {}", attempt, delay, url, error);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
     }
 }
+
 impl<X: X509Iterator> Default for X509Client<X>
 where
     X509ClientError: From<X::X509IteratorError>,
 {
     /// Instantiate X509 Client with default configuration. Defaults are:
     /// ```
-    /// use x509_client::X509ClientConfiguration;
+    /// use x509_client::{
+    ///     X509ArchiveConfiguration, X509BatchConfiguration, X509ClientConfiguration,
+    ///     X509CompressionConfiguration, X509RedirectConfiguration, X509SchemePolicy,
+    ///     X509TypeConfiguration,
+    /// };
     ///
     /// X509ClientConfiguration {
     ///         strict: false,
-    ///         files: false,
+    ///         sniff: None,
+    ///         types: X509TypeConfiguration::default(),
+    ///         schemes: X509SchemePolicy::Deny(vec!["file".to_string()]),
     ///         limit: None,
+    ///         recursive: false,
+    ///         revalidate: false,
+    ///         cache: None,
+    ///         disk_cache: None,
+    ///         retry: None,
+    ///         connect_timeout: None,
+    ///         read_timeout: None,
+    ///         timeout: None,
+    ///         stagger: None,
+    ///         batch: X509BatchConfiguration {
+    ///             concurrency: 16,
+    ///             per_host: 4,
+    ///         },
+    ///         redirect: X509RedirectConfiguration {
+    ///             max: 10,
+    ///             allow_downgrade: true,
+    ///             allow_cross_host: true,
+    ///         },
+    ///         egress: None,
+    ///         compression: X509CompressionConfiguration {
+    ///             max_size: 16 * 1024 * 1024,
+    ///         },
+    ///         archive: X509ArchiveConfiguration {
+    ///             max_entries: 1024,
+    ///             max_size: 16 * 1024 * 1024,
+    ///         },
     ///         http_client: None
     /// };
     /// ```
@@ -180,3 +1157,33 @@ where
         Self::new(X509ClientConfiguration::default())
     }
 }
+
+/// Response of the deserialized `certificates` and the `payload` metadata, the time taken is set once complete
+fn response<X>(
+    url: &Url,
+    certificates: X,
+    size: usize,
+    formats: Vec<X509DocumentFormat>,
+    payload: X509Payload,
+) -> X509Response<X> {
+    X509Response {
+        certificates,
+        url: payload.redirects.last().unwrap_or(url).clone(),
+        redirects: payload.redirects,
+        status: payload.status,
+        headers: payload.headers,
+        size,
+        formats,
+        errors: payload.errors,
+        elapsed: Duration::ZERO,
+    }
+}
+
+/// Per-host batch limit key, host and port
+fn batch_host(url: &Url) -> Option<String> {
+    let host = url.host_str()?;
+    Some(match url.port_or_known_default() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    })
+}
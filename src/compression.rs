@@ -0,0 +1,119 @@
+use crate::api::{X509Document, X509Hint};
+use crate::provided::transport::check_limit;
+use crate::{X509ClientError, X509ClientResult};
+use log::debug;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Compression format of a document
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum X509Compression {
+    Gzip,
+    /// zlib-wrapped deflate
+    Deflate,
+    Zstd,
+    Bzip2,
+}
+
+impl X509Compression {
+    /// Compression format of the filename extension
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_string_lossy().to_lowercase().as_str() {
+            "gz" | "tgz" => Some(Self::Gzip),
+            "zz" => Some(Self::Deflate),
+            "zst" => Some(Self::Zstd),
+            "bz2" => Some(Self::Bzip2),
+            _ => None,
+        }
+    }
+
+    /// Compression format of the leading magic bytes
+    fn from_magic(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0x1f, 0x8b, ..] => Some(Self::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Self::Zstd),
+            [b'B', b'Z', b'h', b'1'..=b'9', ..] => Some(Self::Bzip2),
+            // deflate method and 32K window, no preset dictionary, header checksum
+            [0x78, flags, ..]
+                if flags & 0x20 == 0 && (0x7800 + *flags as u16).is_multiple_of(31) =>
+            {
+                Some(Self::Deflate)
+            }
+            _ => None,
+        }
+    }
+
+    fn decompress(self, bytes: &[u8], limit: usize) -> X509ClientResult<Vec<u8>> {
+        match self {
+            Self::Gzip => read(flate2::read::MultiGzDecoder::new(bytes), limit),
+            Self::Deflate => read(flate2::read::ZlibDecoder::new(bytes), limit),
+            Self::Zstd => read(
+                ruzstd::decoding::StreamingDecoder::new(bytes)
+                    .map_err(|e| X509ClientError::Error(e.to_string()))?,
+                limit,
+            ),
+            Self::Bzip2 => read(bzip2_rs::DecoderReader::new(bytes), limit),
+        }
+    }
+}
+
+/// Decompress `document` if compressed, according to its magic bytes or filename extension.
+/// The decompressed size is subject to `limit`, bounded even if no transfer limit is configured. The hint of the decompressed document is the filename without the compression extension,
+/// documents only detected by magic bytes failing to decompress are returned unchanged.
+pub(crate) fn decompress(document: X509Document, limit: usize) -> X509ClientResult<X509Document> {
+    let extension = match &document.hint {
+        X509Hint::Path(path) => X509Compression::from_path(path),
+        _ => None,
+    };
+    let compression = match X509Compression::from_magic(&document.bytes).or(extension) {
+        Some(compression) => compression,
+        None => return Ok(document),
+    };
+
+    let bytes = match compression.decompress(&document.bytes, limit) {
+        Ok(bytes) => bytes,
+        Err(e @ X509ClientError::LimitExceeded { .. }) => return Err(e),
+        Err(_) if extension.is_none() => {
+            debug!(target:"x509-client", "not {:?} compressed, parsing as is", compression);
+            return Ok(document);
+        }
+        Err(e) => {
+            return Err(X509ClientError::Error(format!(
+                "cannot decompress {:?}: {}",
+                compression, e
+            )))
+        }
+    };
+
+    let hint = match document.hint {
+        X509Hint::Path(path) if extension.is_some() => X509Hint::Path(inner_path(&path)),
+        hint => hint,
+    };
+    Ok(X509Document {
+        hint,
+        bytes: bytes.into(),
+        path: document.path,
+    })
+}
+
+/// True if the leading bytes are the magic bytes of a compression format
+pub(crate) fn is_compressed(bytes: &[u8]) -> bool {
+    X509Compression::from_magic(bytes).is_some()
+}
+
+/// `path` without its compression extension, e.g. `ca.pem` for `ca.pem.gz` and `ca.tar` for `ca.tgz`
+pub(crate) fn inner_path(path: &Path) -> PathBuf {
+    match path.extension().map(|e| e.to_string_lossy().to_lowercase()) {
+        Some(extension) if extension == "tgz" => path.with_extension("tar"),
+        _ if X509Compression::from_path(path).is_some() => path.with_extension(""),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Read at most `limit + 1` bytes, failing if `limit` is exceeded
+fn read(reader: impl Read, limit: usize) -> X509ClientResult<Vec<u8>> {
+    let mut data = vec![];
+    reader.take(limit as u64 + 1).read_to_end(&mut data)?;
+    check_limit(data.len(), Some(limit))?;
+    Ok(data)
+}
@@ -0,0 +1,26 @@
+/// Tag, content and remaining bytes of the leading DER element, None if malformed or truncated.
+/// Multi-byte tags and indefinite lengths are not DER encodings of the supported types.
+pub(crate) fn tlv(src: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, src) = src.split_first()?;
+    if tag & 0x1f == 0x1f {
+        return None;
+    }
+
+    let (&first, src) = src.split_first()?;
+    let (length, src) = match first {
+        0..=0x7f => (first as usize, src),
+        0x81..=0x84 => {
+            let count = (first & 0x7f) as usize;
+            let bytes = src.get(..count)?;
+            let length = bytes.iter().fold(0usize, |l, b| l << 8 | *b as usize);
+            (length, &src[count..])
+        }
+        _ => return None,
+    };
+
+    if length > src.len() {
+        return None;
+    }
+    let (content, rest) = src.split_at(length);
+    Some((tag, content, rest))
+}
@@ -0,0 +1,230 @@
+use crate::{X509ClientError, X509ClientResult, X509EgressError};
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use url::{Host, Url};
+
+/// Egress policy, restricting the hosts, ports and addresses the client connects to.
+/// Intended for urls of untrusted origin, e.g. the AIA urls of user-submitted certificates.
+#[derive(Clone, Debug)]
+pub struct X509EgressPolicy {
+    /// If not empty, only these hosts are permitted. A domain also permits its subdomains.
+    pub allow_hosts: Vec<String>,
+    /// Hosts never permitted, takes precedence over `allow_hosts`. A domain also denies its subdomains.
+    pub deny_hosts: Vec<String>,
+    /// If not empty, only these ports are permitted. Urls without port use the scheme default port, urls of unknown default port are denied.
+    pub allow_ports: Vec<u16>,
+    /// If true, deny unspecified, loopback, link-local, private, shared, unique-local, benchmarking, multicast
+    /// and IETF protocol assignment addresses, along with the IPv4 addresses embedded in IPv4-mapped, NAT64, 6to4 and Teredo addresses.
+    /// Checked against the resolved addresses of every connection, including after redirects.
+    pub block_private: bool,
+}
+
+impl Default for X509EgressPolicy {
+    /// Defaults permit any host and port, and block private addresses
+    fn default() -> Self {
+        Self {
+            allow_hosts: vec![],
+            deny_hosts: vec![],
+            allow_ports: vec![],
+            block_private: true,
+        }
+    }
+}
+
+impl X509EgressPolicy {
+    /// Check the host and port of `url`, without resolving the host
+    pub fn check_url(&self, url: &Url) -> X509ClientResult<()> {
+        let host = match url.host() {
+            Some(host) => host,
+            None => return Ok(()),
+        };
+
+        if self
+            .deny_hosts
+            .iter()
+            .any(|entry| host_matches(&host, entry))
+        {
+            return Err(denied(format!("host {} denied", host)));
+        }
+        if !self.allow_hosts.is_empty()
+            && !self
+                .allow_hosts
+                .iter()
+                .any(|entry| host_matches(&host, entry))
+        {
+            return Err(denied(format!("host {} not allowed", host)));
+        }
+
+        if !self.allow_ports.is_empty() {
+            match port(url) {
+                Some(port) if self.allow_ports.contains(&port) => {}
+                Some(port) => return Err(denied(format!("port {} not allowed", port))),
+                None => return Err(denied(format!("unknown port of {}", url))),
+            }
+        }
+
+        match host {
+            Host::Ipv4(ip) => self.check_ip(IpAddr::V4(ip)),
+            Host::Ipv6(ip) => self.check_ip(IpAddr::V6(ip)),
+            Host::Domain(_) => Ok(()),
+        }
+    }
+
+    /// Check a resolved address
+    pub fn check_ip(&self, ip: IpAddr) -> X509ClientResult<()> {
+        if self.block_private && is_private(ip) {
+            return Err(denied(format!("address {} is private", ip)));
+        }
+        Ok(())
+    }
+
+    /// Check the host and port of `url`, and every resolved address of its host
+    pub(crate) async fn check(&self, url: &Url) -> X509ClientResult<()> {
+        self.check_url(url)?;
+        if let Some(Host::Domain(domain)) = url.host() {
+            if self.block_private {
+                self.resolve(domain).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve `host`, failing if any resolved address is denied
+    pub(crate) async fn resolve(&self, host: &str) -> X509ClientResult<Vec<SocketAddr>> {
+        let addrs = tokio::net::lookup_host((host, 0))
+            .await?
+            .collect::<Vec<_>>();
+        for addr in &addrs {
+            self.check_ip(addr.ip())
+                .map_err(|_| denied(format!("host {} resolves to {}", host, addr.ip())))?;
+        }
+        Ok(addrs)
+    }
+}
+
+/// Resolver denying hosts resolving to blocked addresses
+pub(crate) struct X509EgressResolver(pub(crate) X509EgressPolicy);
+
+impl Resolve for X509EgressResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = self.0.clone();
+        Box::pin(async move {
+            match policy.resolve(name.as_str()).await {
+                Ok(addrs) => Ok(Box::new(addrs.into_iter()) as Addrs),
+                Err(X509ClientError::EgressDenied(e)) => Err(X509EgressError(e).into()),
+                Err(e) => Err(e.to_string().into()),
+            }
+        })
+    }
+}
+
+/// Explicit port, otherwise the default port of the scheme
+fn port(url: &Url) -> Option<u16> {
+    url.port_or_known_default().or(match url.scheme() {
+        "ldap" => Some(389),
+        "ldaps" => Some(636),
+        #[cfg(feature = "tls")]
+        "tls" => Some(crate::provided::transport::TLS_DEFAULT_PORT),
+        #[cfg(feature = "tls")]
+        scheme => crate::provided::transport::StartTlsProtocol::from_scheme(scheme)
+            .map(|protocol| protocol.default_port()),
+        #[cfg(not(feature = "tls"))]
+        _ => None,
+    })
+}
+
+fn denied(reason: String) -> X509ClientError {
+    X509ClientError::EgressDenied(reason)
+}
+
+/// Case-insensitive host match, a domain entry also matches its subdomains
+fn host_matches(host: &Host<&str>, entry: &str) -> bool {
+    let entry = entry.trim_end_matches('.').to_ascii_lowercase();
+    match host {
+        Host::Domain(domain) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain == entry || domain.ends_with(&format!(".{}", entry))
+        }
+        Host::Ipv4(ip) => entry.parse::<IpAddr>() == Ok(IpAddr::V4(*ip)),
+        Host::Ipv6(ip) => {
+            entry
+                .trim_matches(|c| c == '[' || c == ']')
+                .parse::<IpAddr>()
+                == Ok(IpAddr::V6(*ip))
+        }
+    }
+}
+
+fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_private_v4(ip),
+        IpAddr::V6(ip) => match ip
+            .to_ipv4_mapped()
+            .or_else(|| nat64(ip))
+            .or_else(|| six_to_four(ip))
+            .or_else(|| teredo(ip))
+        {
+            Some(ip) => is_private_v4(ip),
+            None => is_private_v6(ip),
+        },
+    }
+}
+
+fn is_private_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_private()
+        || ip.is_broadcast()
+        // multicast 224.0.0.0/4
+        || ip.is_multicast()
+        // "this network" 0.0.0.0/8
+        || a == 0
+        // shared address space 100.64.0.0/10
+        || (a == 100 && (b & 0xc0) == 64)
+        // IETF protocol assignments 192.0.0.0/24
+        || (a == 192 && b == 0 && c == 0)
+        // benchmarking 198.18.0.0/15
+        || (a == 198 && (b & 0xfe) == 18)
+}
+
+/// IPv4 address of a NAT64 well-known prefix 64:ff9b::/96 address
+fn nat64(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    match ip.segments() {
+        [0x64, 0xff9b, 0, 0, 0, 0, high, low] => {
+            Some(Ipv4Addr::from(((high as u32) << 16) | low as u32))
+        }
+        _ => None,
+    }
+}
+
+/// IPv4 address of a 6to4 2002::/16 address
+fn six_to_four(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    match ip.segments() {
+        [0x2002, high, low, ..] => Some(Ipv4Addr::from(((high as u32) << 16) | low as u32)),
+        _ => None,
+    }
+}
+
+/// Client IPv4 address of a Teredo 2001::/32 address, stored inverted in the last 32 bits
+fn teredo(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    match ip.segments() {
+        [0x2001, 0, .., high, low] => Some(Ipv4Addr::from(!(((high as u32) << 16) | low as u32))),
+        _ => None,
+    }
+}
+
+fn is_private_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // unique-local fc00::/7
+        || (first & 0xfe00) == 0xfc00
+        // link-local fe80::/10
+        || (first & 0xffc0) == 0xfe80
+        // deprecated site-local fec0::/10
+        || (first & 0xffc0) == 0xfec0
+}
@@ -1,13 +1,31 @@
 #![doc = include_str!("../README.md")]
+// the README quotes RFC 7468 as wrapped in the RFC
+#![allow(clippy::doc_lazy_continuation)]
 
+#[cfg(feature = "blocking")]
+pub use blocking::*;
 pub use client::*;
+pub use egress::*;
+pub use parse::X509Type;
 pub use reqwest;
 pub use result::*;
+pub use sniff::{X509Confidence, X509Sniff};
 
 pub mod api;
+#[cfg(feature = "archive")]
+mod archive;
+#[cfg(feature = "blocking")]
+mod blocking;
+mod cache;
 mod client;
+#[cfg(feature = "compression")]
+mod compression;
+mod der;
+mod egress;
 mod parse;
 mod result;
+mod sniff;
+mod stream;
 
 pub mod provided;
 
@@ -1,29 +1,60 @@
-use crate::api::X509Iterator;
+use crate::api::{X509Hint, X509Iterator};
+use crate::sniff::{X509Confidence, X509Sniff};
 use crate::{X509ClientError, X509ClientResult};
 use http::header::CONTENT_TYPE;
 use http::HeaderMap;
+use log::debug;
 use std::marker::PhantomData;
 use std::path::Path;
 
+/// Certificate encoding
 #[derive(Clone, PartialEq, Debug)]
+#[non_exhaustive]
 pub enum X509Type {
+    /// Single DER encoded certificate
     Cer,
+    /// DER encoded PKCS7 certificate bundle
     Pkcs7,
+    /// PEM encoded certificates
     Pem,
+    /// DER encoded certificate revocation list, deserialized as no certificates
+    Crl,
+    /// DER encoded PKCS12 keystore, only determined by content sniffing and not deserialized
+    Pkcs12,
+    /// Undetermined
     Unknown,
 }
 
+/// Default media types, see [`crate::X509TypeConfiguration`]
+pub(crate) const MEDIA_TYPES: [(&str, X509Type); 9] = [
+    ("application/pkix-cert", X509Type::Cer),
+    ("application/x-x509-ca-cert", X509Type::Cer),
+    ("application/x-x509-user-cert", X509Type::Cer),
+    ("application/pkcs7-mime", X509Type::Pkcs7),
+    ("application/x-pkcs7-mime", X509Type::Pkcs7),
+    ("application/x-pkcs7-certificates", X509Type::Pkcs7),
+    ("application/pem-certificate-chain", X509Type::Pem),
+    ("application/x-pem-file", X509Type::Pem),
+    ("application/pkix-crl", X509Type::Crl),
+];
+
+/// Default filename extensions, see [`crate::X509TypeConfiguration`]
+pub(crate) const EXTENSIONS: [(&str, X509Type); 3] = [
+    ("cer", X509Type::Cer),
+    ("p7c", X509Type::Pkcs7),
+    ("pem", X509Type::Pem),
+];
+
 impl From<&Path> for X509Type {
     fn from(path: &Path) -> Self {
-        match path.extension() {
-            None => X509Type::Unknown,
-            Some(extension) => match extension.to_string_lossy().to_lowercase().as_str() {
-                "cer" => X509Type::Cer,
-                "p7c" => X509Type::Pkcs7,
-                "pem" => X509Type::Pem,
-                _ => X509Type::Unknown,
-            },
-        }
+        extension_type(path, |extension| lookup(&EXTENSIONS, extension))
+    }
+}
+
+impl From<&str> for X509Type {
+    /// Type of the media type, ignoring parameters other than `smime-type`
+    fn from(content_type: &str) -> Self {
+        media_type(content_type, |essence| lookup(&MEDIA_TYPES, essence))
     }
 }
 
@@ -32,21 +63,28 @@ impl From<&HeaderMap> for X509Type {
         match headers.get(CONTENT_TYPE) {
             None => X509Type::Unknown,
             Some(header) => match header.to_str() {
-                Ok(header) => match header.to_lowercase().as_str() {
-                    "application/pkix-cert" => X509Type::Cer,
-                    "application/pkcs7-mime" => X509Type::Pkcs7,
-                    "application/pem-certificate-chain" => X509Type::Pem,
-                    _ => X509Type::Unknown,
-                },
+                Ok(header) => X509Type::from(header),
                 Err(_) => X509Type::Unknown,
             },
         }
     }
 }
 
+impl From<&X509Hint> for X509Type {
+    fn from(hint: &X509Hint) -> Self {
+        match hint {
+            X509Hint::None => X509Type::Unknown,
+            X509Hint::ContentType(content_type) => X509Type::from(content_type.as_str()),
+            X509Hint::Path(path) => X509Type::from(path.as_path()),
+            X509Hint::Type(format) => format.clone(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct X509Parse<X: X509Iterator> {
     strict: bool,
+    sniff: Option<X509Confidence>,
     x509_iterator: PhantomData<X>,
 }
 
@@ -57,13 +95,55 @@ where
     pub fn new(strict: bool) -> Self {
         Self {
             strict,
+            sniff: None,
             x509_iterator: PhantomData,
         }
     }
 
-    pub fn parse<T: AsRef<[u8]>>(&self, hint: &X509Type, src: T) -> X509ClientResult<X> {
+    /// Sniff the content, see [`X509Parse::hint`]
+    pub fn with_sniff(mut self, sniff: Option<X509Confidence>) -> Self {
+        self.sniff = sniff;
+        self
+    }
+
+    /// True if only the hinted type is attempted
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Minimum confidence of a sniffed type taking precedence over the transport hint, None if content sniffing is disabled
+    pub fn sniff(&self) -> Option<X509Confidence> {
+        self.sniff
+    }
+
+    /// Type to attempt first: the sniffed type if sniffed with at least the configured confidence, otherwise `hint`.
+    /// Returns the sniffed type, if content sniffing is enabled and determined.
+    pub fn hint(&self, hint: X509Type, src: &[u8]) -> (X509Type, Option<X509Sniff>) {
+        let min = match self.sniff {
+            Some(min) => min,
+            None => return (hint, None),
+        };
+
+        let sniffed = X509Sniff::sniff(src);
+        match &sniffed {
+            Some(sniffed) if sniffed.confidence >= min => {
+                if sniffed.format != hint {
+                    debug!(target:"x509-client", "sniffed {:?} ({:?}), hinted {:?}", sniffed.format, sniffed.confidence, hint);
+                }
+                (sniffed.format.clone(), Some(sniffed.clone()))
+            }
+            _ => (hint, sniffed),
+        }
+    }
+
+    /// Parse, returning the type the certificates were deserialized as
+    pub fn parse<T: AsRef<[u8]>>(
+        &self,
+        hint: &X509Type,
+        src: T,
+    ) -> X509ClientResult<(X, X509Type)> {
         if self.strict {
-            return Self::parse_strict(hint, src);
+            return Ok((Self::parse_strict(hint, src)?, hint.clone()));
         }
 
         Self::parse_relaxed(hint, src)
@@ -74,6 +154,12 @@ where
             X509Type::Cer => X::from_cer(src)?,
             X509Type::Pkcs7 => X::from_pkcs7(src)?,
             X509Type::Pem => X::from_pem(src)?,
+            X509Type::Crl => X::from_iter(std::iter::empty()),
+            X509Type::Pkcs12 => {
+                return Err(X509ClientError::Error(
+                    "pkcs12 keystore not supported".to_string(),
+                ))
+            }
             X509Type::Unknown => {
                 return Err(X509ClientError::Error(
                     "unknown type not permitted".to_string(),
@@ -83,30 +169,62 @@ where
         Ok(r)
     }
 
-    fn parse_relaxed<T: AsRef<[u8]>>(hint: &X509Type, src: T) -> X509ClientResult<X> {
-        // try hint first
-        if let Ok(v) = Self::parse_strict(hint, src.as_ref()) {
-            return Ok(v);
-        }
-
-        if hint != &X509Type::Cer {
-            if let Ok(v) = X::from_cer(src.as_ref()) {
-                return Ok(v);
+    fn parse_relaxed<T: AsRef<[u8]>>(hint: &X509Type, src: T) -> X509ClientResult<(X, X509Type)> {
+        // try hint first, a CRL always "parses" as no certificates and is only assumed if nothing else parses,
+        // e.g. a certificate mislabelled `application/pkix-crl`
+        if hint != &X509Type::Crl {
+            if let Ok(v) = Self::parse_strict(hint, src.as_ref()) {
+                return Ok((v, hint.clone()));
             }
         }
 
-        if hint != &X509Type::Pkcs7 {
-            if let Ok(v) = X::from_pkcs7(src.as_ref()) {
-                return Ok(v);
+        // pem last, an "empty" pem document is valid
+        for fallback in [X509Type::Cer, X509Type::Pkcs7, X509Type::Pem] {
+            if hint == &fallback {
+                continue;
+            }
+            if let Ok(v) = Self::parse_strict(&fallback, src.as_ref()) {
+                return Ok((v, fallback));
             }
         }
 
-        if hint != &X509Type::Pem {
-            if let Ok(v) = X::from_pem(src.as_ref()) {
-                return Ok(v);
-            }
+        match hint {
+            X509Type::Crl => Ok((Self::parse_strict(hint, src)?, X509Type::Crl)),
+            _ => Err(X509ClientError::Error("failed to parse".to_string())),
         }
+    }
+}
 
-        Err(X509ClientError::Error("failed to parse".to_string()))
+/// Type of the lowercase filename extension of `path` according to `types`
+pub(crate) fn extension_type(path: &Path, types: impl Fn(&str) -> Option<X509Type>) -> X509Type {
+    path.extension()
+        .and_then(|extension| types(&extension.to_string_lossy().to_lowercase()))
+        .unwrap_or(X509Type::Unknown)
+}
+
+/// Type of the lowercase media type essence of `content_type` according to `types`, ignoring parameters other than `smime-type`
+pub(crate) fn media_type(content_type: &str, types: impl Fn(&str) -> Option<X509Type>) -> X509Type {
+    let mut parts = content_type.split(';');
+    let essence = parts.next().unwrap_or_default().trim().to_lowercase();
+    let smime_type = parts
+        .filter_map(|parameter| parameter.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("smime-type"))
+        .map(|(_, value)| value.trim().trim_matches('"').to_lowercase());
+
+    match types(&essence) {
+        // certificates of a signed-data message are deserialized too, other S/MIME messages are not certificate bundles
+        Some(X509Type::Pkcs7) => match smime_type.as_deref() {
+            None | Some("certs-only") | Some("signed-data") => X509Type::Pkcs7,
+            Some(_) => X509Type::Unknown,
+        },
+        Some(x509_type) => x509_type,
+        None => X509Type::Unknown,
     }
 }
+
+fn lookup(table: &[(&str, X509Type)], key: &str) -> Option<X509Type> {
+    table
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, x509_type)| x509_type.clone())
+}
@@ -1,6 +1,7 @@
 pub mod debug;
 #[cfg(feature = "openssl")]
 pub mod openssl;
+pub mod transport;
 
 #[cfg(feature = "default")]
 pub mod default;
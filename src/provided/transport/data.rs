@@ -0,0 +1,34 @@
+use crate::api::{X509Hint, X509Payload, X509Transport, X509TransportOptions};
+use crate::provided::transport::check_limit;
+use crate::{X509ClientError, X509ClientResult};
+use data_url::DataUrl;
+use url::Url;
+
+/// RFC 2397 `data` scheme transport.
+/// Supports base64 and percent-encoded data, the media type is used as the encoding hint.
+#[derive(Clone, Default)]
+pub struct DataX509Transport;
+
+impl X509Transport for DataX509Transport {
+    async fn fetch(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509Payload> {
+        let limit = options.limit;
+        let data_url = DataUrl::process(url.as_str()).map_err(|e| {
+            X509ClientError::UrlParseError(format!("cannot parse data url: {:?}", e))
+        })?;
+
+        let (data, _) = data_url
+            .decode_to_vec()
+            .map_err(|e| X509ClientError::Error(format!("cannot decode data url: {:?}", e)))?;
+
+        check_limit(data.len(), limit)?;
+
+        Ok(X509Payload::new(
+            X509Hint::ContentType(data_url.mime_type().to_string()),
+            data,
+        ))
+    }
+}
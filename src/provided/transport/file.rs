@@ -0,0 +1,252 @@
+use crate::api::{
+    X509Document, X509DocumentError, X509Hint, X509Payload, X509StreamPayload, X509Transport,
+    X509TransportOptions,
+};
+#[cfg(feature = "compression")]
+use crate::compression::inner_path;
+use crate::parse::X509Type;
+use crate::provided::transport::{check_limit, with_timeout};
+use crate::{X509ClientError, X509ClientResult, X509TypeConfiguration};
+use bytes::Bytes;
+use futures::stream::{self, StreamExt};
+use log::warn;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs;
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+use url::Url;
+
+/// Size of the chunks of streamed files
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// `File` scheme transport.
+/// Directory urls load every recognised certificate file: configured filename extensions,
+/// and OpenSSL `c_rehash`-style `<hash>.N` entries. Symlinks are followed.
+/// Files failing to load are reported in the payload errors.
+#[derive(Clone, Default)]
+pub struct FileX509Transport {
+    recursive: bool,
+    types: X509TypeConfiguration,
+}
+
+impl FileX509Transport {
+    /// If `recursive` is true, directories are loaded recursively
+    pub fn new(recursive: bool) -> Self {
+        Self {
+            recursive,
+            types: Default::default(),
+        }
+    }
+
+    /// Filename extensions of the directory entries loaded, defaults to the default extensions of [`X509TypeConfiguration`]
+    pub fn with_types(mut self, types: X509TypeConfiguration) -> Self {
+        self.types = types;
+        self
+    }
+
+    async fn read_dir(
+        &self,
+        dir: &Path,
+        limit: Option<usize>,
+        read_timeout: Option<Duration>,
+    ) -> X509ClientResult<X509Payload> {
+        let mut payload = X509Payload {
+            partial: true,
+            ..Default::default()
+        };
+
+        let mut visited = HashSet::new();
+        let mut pending = vec![fs::canonicalize(dir).await?];
+
+        while let Some(dir) = pending.pop() {
+            if !visited.insert(dir.clone()) {
+                continue;
+            }
+
+            let mut entries = vec![];
+            let mut read_dir = fs::read_dir(&dir).await?;
+            while let Some(entry) = read_dir.next_entry().await? {
+                entries.push(entry.path());
+            }
+            entries.sort();
+
+            for path in entries {
+                let canonical = match fs::canonicalize(&path).await {
+                    Ok(canonical) => canonical,
+                    Err(e) => {
+                        payload.errors.push(document_error(path, e.into()));
+                        continue;
+                    }
+                };
+
+                let metadata = match fs::metadata(&canonical).await {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        payload.errors.push(document_error(path, e.into()));
+                        continue;
+                    }
+                };
+
+                if metadata.is_dir() {
+                    if self.recursive {
+                        pending.push(canonical);
+                    }
+                    continue;
+                }
+
+                let hint = match recognise(&path, &canonical, &self.types) {
+                    Some(hint) => hint,
+                    None => continue,
+                };
+
+                // hashed entries usually link to certificates in the same directory
+                if !visited.insert(canonical.clone()) {
+                    continue;
+                }
+
+                let remaining = limit.map(|limit| limit.saturating_sub(payload.len()));
+                match with_timeout(read_timeout, read_file(&canonical, remaining)).await {
+                    Err(X509ClientError::LimitExceeded { size, .. }) => {
+                        return Err(X509ClientError::LimitExceeded {
+                            size: payload.len() + size,
+                            limit: limit.unwrap_or_default(),
+                        })
+                    }
+                    Ok(data) => payload
+                        .documents
+                        .push(X509Document::new(hint, data).with_path(path)),
+                    Err(e) => payload.errors.push(document_error(path, e)),
+                }
+            }
+        }
+
+        Ok(payload)
+    }
+}
+
+impl X509Transport for FileX509Transport {
+    async fn fetch(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509Payload> {
+        let limit = options.limit;
+        let path = url
+            .to_file_path()
+            .map_err(|_| X509ClientError::Error(format!("cannot parse file url {}", url)))?;
+
+        if fs::metadata(&path).await?.is_dir() {
+            return self.read_dir(&path, limit, options.read_timeout).await;
+        }
+
+        let data = with_timeout(options.read_timeout, read_file(&path, limit)).await?;
+        Ok(X509Payload::new(X509Hint::Path(path), data))
+    }
+
+    /// Streams files in chunks, directories are transferred complete
+    async fn fetch_stream(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509StreamPayload> {
+        let limit = options.limit;
+        let read_timeout = options.read_timeout;
+        let path = url
+            .to_file_path()
+            .map_err(|_| X509ClientError::Error(format!("cannot parse file url {}", url)))?;
+
+        if fs::metadata(&path).await?.is_dir() {
+            return Ok(X509StreamPayload::Complete(
+                self.read_dir(&path, limit, read_timeout).await?,
+            ));
+        }
+
+        let file = File::open(&path).await?;
+        check_limit(file.metadata().await?.len() as usize, limit)?;
+
+        let chunks = stream::try_unfold((file, 0), move |(mut file, size)| async move {
+            let mut buf = vec![0; CHUNK_SIZE];
+            let n = with_timeout(read_timeout, async { Ok(file.read(&mut buf).await?) }).await?;
+            if n == 0 {
+                return Ok(None);
+            }
+            buf.truncate(n);
+            let size = size + n;
+            check_limit(size, limit)?;
+            Ok(Some((Bytes::from(buf), (file, size))))
+        });
+
+        Ok(X509StreamPayload::Chunks(
+            X509Hint::Path(path),
+            chunks.boxed(),
+        ))
+    }
+}
+
+/// Read a file, failing early if the file size already exceeds `limit`.
+/// Reads at most `limit + 1` bytes, special files (e.g. `/dev/zero`) may report a size of zero.
+async fn read_file(path: &Path, limit: Option<usize>) -> X509ClientResult<Vec<u8>> {
+    let file = File::open(path).await?;
+    let mut data = vec![];
+    match limit {
+        None => {
+            let mut file = file;
+            file.read_to_end(&mut data).await?;
+        }
+        Some(limit) => {
+            check_limit(file.metadata().await?.len() as usize, Some(limit))?;
+            file.take(limit as u64 + 1).read_to_end(&mut data).await?;
+            check_limit(data.len(), Some(limit))?;
+        }
+    }
+    Ok(data)
+}
+
+/// Encoding hint for a recognised certificate file, None if the file is not recognised
+fn recognise(path: &Path, canonical: &Path, types: &X509TypeConfiguration) -> Option<X509Hint> {
+    for path in [path, canonical] {
+        // compressed certificate files, e.g. `ca.pem.gz`
+        #[cfg(feature = "compression")]
+        let inner = inner_path(path);
+        #[cfg(not(feature = "compression"))]
+        let inner = path.to_path_buf();
+
+        if types.extension(&inner) != X509Type::Unknown {
+            return Some(X509Hint::Path(path.to_path_buf()));
+        }
+    }
+
+    // `c_rehash` entries are PEM-encoded
+    if is_hashed(path) {
+        return Some(X509Hint::Type(X509Type::Pem));
+    }
+
+    None
+}
+
+/// True for `c_rehash`-style certificate entries, `<8 hex digits>.<N>`. CRL entries `<hash>.r<N>` are excluded.
+fn is_hashed(path: &Path) -> bool {
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => return false,
+    };
+    match name.split_once('.') {
+        Some((hash, n)) => {
+            hash.len() == 8
+                && hash.chars().all(|c| c.is_ascii_hexdigit())
+                && !n.is_empty()
+                && n.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+fn document_error(path: PathBuf, error: X509ClientError) -> X509DocumentError {
+    warn!(target:"x509-client", "cannot load {}: {}", path.display(), error);
+    X509DocumentError {
+        path,
+        error: error.to_string(),
+    }
+}
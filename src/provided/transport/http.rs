@@ -0,0 +1,322 @@
+use crate::api::{
+    X509Freshness, X509Hint, X509Payload, X509StreamPayload, X509Transport, X509TransportOptions,
+    X509Validators,
+};
+use crate::egress::X509EgressResolver;
+use crate::provided::transport::{check_limit, with_timeout};
+use crate::{
+    X509ClientConfiguration, X509ClientError, X509ClientResult, X509EgressPolicy,
+    X509RedirectConfiguration, X509SchemePolicy,
+};
+use bytes::BytesMut;
+use futures::stream::{self, StreamExt};
+use http::header::{
+    CACHE_CONTROL, CONTENT_TYPE, DATE, ETAG, EXPIRES, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+    LAST_MODIFIED, LOCATION, RETRY_AFTER,
+};
+use http::{HeaderMap, HeaderValue, StatusCode};
+use log::{debug, warn};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Response};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use url::Url;
+
+/// Reqwest-based HTTP/S transport.
+/// Redirects are followed by the transport, checking each redirect against the configured restrictions and policies.
+#[derive(Clone, Default)]
+pub struct ReqwestX509Transport {
+    client: Client,
+    redirect: X509RedirectConfiguration,
+    schemes: X509SchemePolicy,
+    egress: Option<X509EgressPolicy>,
+}
+
+impl ReqwestX509Transport {
+    /// Transport using the supplied Reqwest `client`, with the default redirect restrictions and policies
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            ..Default::default()
+        }
+    }
+
+    /// Transport applying the redirect restrictions, scheme and egress policies of `config`.
+    /// If `http_client` is None, the Reqwest client is built to leave redirects to the transport,
+    /// and to check the resolved addresses against the egress policy.
+    /// Otherwise, redirects followed by the configured client itself are only checked once complete.
+    pub fn with_configuration(config: &X509ClientConfiguration) -> Self {
+        let client = match &config.http_client {
+            Some(client) => {
+                if config.egress.is_some() {
+                    warn!(
+                        target:"x509-client",
+                        "egress policy with custom http client, resolved addresses are not checked"
+                    );
+                }
+                client.clone()
+            }
+            None => {
+                let mut builder = Client::builder().redirect(Policy::none());
+                if let Some(egress) = &config.egress {
+                    builder = builder.dns_resolver(Arc::new(X509EgressResolver(egress.clone())));
+                }
+                builder.build().expect("http client cannot be initialized")
+            }
+        };
+        Self {
+            client,
+            redirect: config.redirect.clone(),
+            schemes: config.schemes.clone(),
+            egress: config.egress.clone(),
+        }
+    }
+
+    /// Check the redirect from `from` to `to`, `count` redirects having been followed before
+    pub(crate) fn check_redirect(
+        &self,
+        from: &Url,
+        to: &Url,
+        count: usize,
+    ) -> X509ClientResult<()> {
+        if count >= self.redirect.max {
+            return Err(X509ClientError::RedirectRefused(format!(
+                "more than {} redirects",
+                self.redirect.max
+            )));
+        }
+        if !self.redirect.allow_downgrade && from.scheme() == "https" && to.scheme() == "http" {
+            return Err(X509ClientError::RedirectRefused(format!(
+                "downgrade from {} to {}",
+                from, to
+            )));
+        }
+        if !self.redirect.allow_cross_host && from.host() != to.host() {
+            return Err(X509ClientError::RedirectRefused(format!(
+                "cross-host from {} to {}",
+                from, to
+            )));
+        }
+        if !self.schemes.permits(to.scheme()) {
+            return Err(X509ClientError::SchemeNotPermitted(to.scheme().to_string()));
+        }
+        match &self.egress {
+            Some(egress) => egress.check_url(to),
+            None => Ok(()),
+        }
+    }
+
+    async fn send(&self, url: &Url, options: &X509TransportOptions) -> X509ClientResult<Response> {
+        let mut req = self.client.get(url.as_str());
+        if let Some(validators) = &options.validators {
+            if let Some(etag) = &validators.etag {
+                req = req.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                req = req.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        // reqwest resolves once the response head is received
+        with_timeout(options.connect_timeout, async { Ok(req.send().await?) }).await
+    }
+
+    /// Send the request, following redirects, failing on http error status.
+    /// Returns the final response along with the followed redirects.
+    async fn respond(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<(Response, Vec<Url>)> {
+        let mut url = url.clone();
+        let mut redirects = vec![];
+        let resp = loop {
+            let resp = self.send(&url, options).await?;
+            let location = resp.headers().get(LOCATION).and_then(|h| h.to_str().ok());
+            let location = match location {
+                Some(location)
+                    if resp.status().is_redirection()
+                        && resp.status() != StatusCode::NOT_MODIFIED =>
+                {
+                    location
+                }
+                _ => break resp,
+            };
+
+            let next = url.join(location)?;
+            self.check_redirect(&url, &next, redirects.len())?;
+            debug!(target:"x509-client", "redirect: {} -> {}", url, next);
+            redirects.push(next.clone());
+            url = next;
+        };
+
+        // redirected by a configured http client following redirects itself
+        if resp.url() != &url {
+            let next = resp.url().clone();
+            self.check_redirect(&url, &next, redirects.len())?;
+            redirects.push(next);
+        }
+
+        let status = resp.status();
+        if status.is_client_error() || status.is_server_error() {
+            return Err(X509ClientError::HttpStatus {
+                status: status.as_u16(),
+                retry_after: retry_after(resp.headers()),
+            });
+        }
+
+        Ok((resp, redirects))
+    }
+}
+
+impl X509Transport for ReqwestX509Transport {
+    async fn fetch(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509Payload> {
+        let limit = options.limit;
+        let (mut resp, redirects) = self.respond(url, options).await?;
+        let status = resp.status();
+
+        let header = |name| {
+            resp.headers()
+                .get(name)
+                .and_then(|h: &HeaderValue| h.to_str().ok())
+                .map(str::to_string)
+        };
+        let validators = X509Validators {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        };
+        let freshness = freshness(resp.headers());
+
+        if status == StatusCode::NOT_MODIFIED {
+            return Ok(X509Payload {
+                validators,
+                not_modified: true,
+                freshness,
+                redirects,
+                status: Some(status.as_u16()),
+                headers: resp.headers().clone(),
+                ..Default::default()
+            });
+        }
+
+        let hint = hint(resp.headers());
+
+        if let Some(content_length) = resp.content_length() {
+            check_limit(content_length as usize, limit)?;
+        }
+
+        let headers = resp.headers().clone();
+        let mut buf = BytesMut::new();
+        while let Some(b) =
+            with_timeout(options.read_timeout, async { Ok(resp.chunk().await?) }).await?
+        {
+            buf.extend(b);
+            check_limit(buf.len(), limit)?;
+        }
+        let bytes = buf.freeze();
+
+        Ok(X509Payload {
+            validators,
+            freshness,
+            redirects,
+            status: Some(status.as_u16()),
+            headers,
+            ..X509Payload::new(hint, bytes)
+        })
+    }
+
+    /// Streams the body of the final response, without cache validators
+    async fn fetch_stream(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509StreamPayload> {
+        let limit = options.limit;
+        let read_timeout = options.read_timeout;
+        let options = X509TransportOptions {
+            validators: None,
+            ..options.clone()
+        };
+        let (resp, _) = self.respond(url, &options).await?;
+
+        let hint = hint(resp.headers());
+        if let Some(content_length) = resp.content_length() {
+            check_limit(content_length as usize, limit)?;
+        }
+
+        let chunks = stream::try_unfold((resp, 0), move |(mut resp, size)| async move {
+            match with_timeout(read_timeout, async { Ok(resp.chunk().await?) }).await? {
+                None => Ok(None),
+                Some(chunk) => {
+                    let size = size + chunk.len();
+                    check_limit(size, limit)?;
+                    Ok(Some((chunk, (resp, size))))
+                }
+            }
+        });
+
+        Ok(X509StreamPayload::Chunks(hint, chunks.boxed()))
+    }
+}
+
+/// Encoding hint of the `Content-Type` http header
+fn hint(headers: &HeaderMap) -> X509Hint {
+    match headers.get(CONTENT_TYPE).map(|h| h.to_str()) {
+        Some(Ok(content_type)) => X509Hint::ContentType(content_type.to_string()),
+        _ => X509Hint::None,
+    }
+}
+
+/// Delay of the `Retry-After` http header, either delay-seconds or an http date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let retry_after = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    match retry_after.parse() {
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        Err(_) => httpdate::parse_http_date(retry_after)
+            .ok()
+            .map(|date| date.duration_since(SystemTime::now()).unwrap_or_default()),
+    }
+}
+
+/// Caching directive of the `Cache-Control` and `Expires` http headers.
+/// `no-store` takes precedence over `max-age`, which takes precedence over `Expires`.
+/// `no-cache` and invalid `Expires` dates are treated as already expired.
+pub(crate) fn freshness(headers: &HeaderMap) -> X509Freshness {
+    let header = |name| headers.get(name).and_then(|h| h.to_str().ok());
+
+    let mut max_age = None;
+    for directive in header(CACHE_CONTROL).unwrap_or_default().split(',') {
+        let directive = directive.trim().to_ascii_lowercase();
+        match directive.split_once('=') {
+            None if directive == "no-store" => return X509Freshness::NoStore,
+            None if directive == "no-cache" => max_age = Some(Duration::ZERO),
+            Some(("max-age", secs)) => {
+                if let Ok(secs) = secs.trim_matches('"').parse() {
+                    max_age = max_age.or(Some(Duration::from_secs(secs)));
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(max_age) = max_age {
+        return X509Freshness::MaxAge(max_age);
+    }
+
+    match header(EXPIRES) {
+        None => X509Freshness::Unspecified,
+        Some(expires) => {
+            let now = header(DATE)
+                .and_then(|date| httpdate::parse_http_date(date).ok())
+                .unwrap_or_else(SystemTime::now);
+            let max_age = httpdate::parse_http_date(expires)
+                .ok()
+                .and_then(|expires| expires.duration_since(now).ok())
+                .unwrap_or_default();
+            X509Freshness::MaxAge(max_age)
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::api::{X509Document, X509Hint, X509Payload, X509Transport, X509TransportOptions};
+use crate::der::tlv;
+use crate::provided::transport::{check_limit, with_timeout};
+use crate::{X509ClientError, X509ClientResult, X509EgressPolicy};
+use ldap3::{get_url_params, Ldap, LdapConnAsync, LdapConnSettings, SearchEntry};
+use url::{Host, Url};
+
+const CA_CERTIFICATE: &str = "cACertificate;binary";
+const CROSS_CERTIFICATE_PAIR: &str = "crossCertificatePair;binary";
+const USER_CERTIFICATE: &str = "userCertificate;binary";
+
+/// `ldap` and `ldaps` scheme transport.
+/// Performs the RFC 4516 URL search, returning the values of the
+/// `cACertificate`, `crossCertificatePair` and `userCertificate` attributes.
+/// If the URL does not specify attributes, all three are requested.
+#[derive(Clone, Default)]
+pub struct LdapX509Transport {
+    egress: Option<X509EgressPolicy>,
+}
+
+impl LdapX509Transport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// If Some, connect to the addresses resolved and checked by the egress policy, the host is not resolved again.
+    /// `ldaps` urls are then refused with `X509ClientError::EgressDenied`: the LDAP client verifies the server
+    /// certificate against the host it connects to, which would be the address rather than the url host.
+    pub fn with_egress(mut self, egress: Option<X509EgressPolicy>) -> Self {
+        self.egress = egress;
+        self
+    }
+
+    async fn connect(&self, url: &Url, settings: LdapConnSettings) -> X509ClientResult<Ldap> {
+        let egress = match &self.egress {
+            Some(egress) => egress,
+            None => {
+                let (conn, ldap) = LdapConnAsync::from_url_with_settings(settings, url).await?;
+                ldap3::drive!(conn);
+                return Ok(ldap);
+            }
+        };
+        if url.scheme() == "ldaps" {
+            return Err(X509ClientError::EgressDenied(format!(
+                "{}, server certificate cannot be verified against the checked addresses",
+                url
+            )));
+        }
+
+        let host = match url.host() {
+            Some(Host::Ipv6(ip)) => ip.to_string(),
+            Some(host) => host.to_string(),
+            None => {
+                return Err(X509ClientError::UrlParseError(format!(
+                    "missing host {}",
+                    url
+                )))
+            }
+        };
+
+        let addrs = egress.resolve(&host).await?;
+        let mut error = None;
+        for addr in addrs {
+            let mut target = url.clone();
+            if target.set_ip_host(addr.ip()).is_err() {
+                continue;
+            }
+            match LdapConnAsync::from_url_with_settings(settings.clone(), &target).await {
+                Ok((conn, ldap)) => {
+                    ldap3::drive!(conn);
+                    return Ok(ldap);
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(match error {
+            Some(e) => e.into(),
+            None => X509ClientError::Error(format!("cannot resolve {}", host)),
+        })
+    }
+}
+
+impl X509Transport for LdapX509Transport {
+    async fn fetch(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509Payload> {
+        let limit = options.limit;
+        let params = get_url_params(url)?;
+        let attrs = match params.attrs.as_slice() {
+            ["*"] => vec![CA_CERTIFICATE, CROSS_CERTIFICATE_PAIR, USER_CERTIFICATE],
+            attrs => attrs.to_vec(),
+        };
+
+        let mut settings = LdapConnSettings::new();
+        if let Some(connect_timeout) = options.connect_timeout {
+            settings = settings.set_conn_timeout(connect_timeout);
+        }
+        let mut ldap = self.connect(url, settings).await?;
+
+        // entries are checked against the limit as they arrive, rather than once the whole result is buffered
+        let documents = with_timeout(options.read_timeout, async {
+            let mut search = ldap
+                .streaming_search(&params.base, params.scope, &params.filter, attrs)
+                .await?;
+            let mut documents = vec![];
+            let mut size = 0;
+            while let Some(entry) = search.next().await? {
+                for document in entry_documents(SearchEntry::construct(entry))? {
+                    size += document.bytes.len();
+                    check_limit(size, limit)?;
+                    documents.push(document);
+                }
+            }
+            search.finish().await.success()?;
+            Ok(documents)
+        })
+        .await?;
+        let _ = ldap.unbind().await;
+
+        Ok(X509Payload::from(documents))
+    }
+}
+
+/// Certificates of the `cACertificate`, `crossCertificatePair` and `userCertificate` values of `entry`
+fn entry_documents(entry: SearchEntry) -> X509ClientResult<Vec<X509Document>> {
+    let values = entry.bin_attrs.into_iter().chain(
+        entry
+            .attrs
+            .into_iter()
+            .map(|(attr, values)| (attr, values.into_iter().map(String::into_bytes).collect())),
+    );
+
+    let mut documents = vec![];
+    for (attr, values) in values {
+        for value in values {
+            if is_attribute(&attr, CROSS_CERTIFICATE_PAIR) {
+                documents.extend(certificate_pair(&value)?.into_iter().map(cer));
+            } else if is_attribute(&attr, CA_CERTIFICATE) || is_attribute(&attr, USER_CERTIFICATE) {
+                documents.push(cer(value));
+            }
+        }
+    }
+    Ok(documents)
+}
+
+fn cer<T: AsRef<[u8]>>(src: T) -> X509Document {
+    X509Document::new(
+        X509Hint::ContentType("application/pkix-cert".to_string()),
+        src.as_ref().to_vec(),
+    )
+}
+
+/// Compare attribute descriptions, ignoring case and the `binary` option
+fn is_attribute(attr: &str, expected: &str) -> bool {
+    let name = |a: &str| a.split(';').next().unwrap_or_default().to_lowercase();
+    name(attr) == name(expected)
+}
+
+/// Split a DER-encoded `CertificatePair` into its `forward` and `reverse` certificates.
+/// ```text
+/// CertificatePair ::= SEQUENCE {
+///     forward  [0] Certificate OPTIONAL,
+///     reverse  [1] Certificate OPTIONAL }
+/// ```
+fn certificate_pair(src: &[u8]) -> X509ClientResult<Vec<&[u8]>> {
+    let invalid = || X509ClientError::Error("invalid certificate pair".to_string());
+
+    let (tag, mut content, _) = tlv(src).ok_or_else(invalid)?;
+    if tag != 0x30 {
+        return Err(invalid());
+    }
+
+    let mut certificates = vec![];
+    while !content.is_empty() {
+        let (tag, certificate, rest) = tlv(content).ok_or_else(invalid)?;
+        if tag != 0xa0 && tag != 0xa1 {
+            return Err(invalid());
+        }
+        certificates.push(certificate);
+        content = rest;
+    }
+    Ok(certificates)
+}
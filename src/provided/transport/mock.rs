@@ -0,0 +1,127 @@
+use crate::api::{X509Payload, X509StreamPayload, X509Transport, X509TransportOptions};
+use crate::provided::transport::{check_limit, with_timeout};
+use crate::{X509ClientError, X509ClientResult};
+use bytes::Bytes;
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use url::Url;
+
+/// In-memory transport, serves registered payloads and records requested urls.
+/// Payloads registered with validators are reported as not modified if the request supplies matching validators.
+/// Intended for unit tests, no network or file system access is performed.
+#[derive(Clone, Default)]
+pub struct MockX509Transport {
+    payloads: HashMap<Url, X509Payload>,
+    requests: Arc<Mutex<Vec<Url>>>,
+    delay: Option<Duration>,
+    chunk_size: Option<usize>,
+}
+
+impl MockX509Transport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the payload served for `url`
+    pub fn with_payload(mut self, url: Url, payload: X509Payload) -> Self {
+        self.payloads.insert(url, payload);
+        self
+    }
+
+    /// Simulate a slow server, each response is delayed by `delay`, subject to the read timeout
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Stream single-document payloads in chunks of `chunk_size` bytes, subject to the transfer limit.
+    /// Otherwise, payloads are streamed complete.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = Some(chunk_size.max(1));
+        self
+    }
+
+    /// Urls requested so far, in order
+    pub fn requests(&self) -> Vec<Url> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl X509Transport for MockX509Transport {
+    async fn fetch(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509Payload> {
+        let limit = options.limit;
+        self.requests.lock().unwrap().push(url.clone());
+
+        if let Some(delay) = self.delay {
+            with_timeout(options.read_timeout, async {
+                tokio::time::sleep(delay).await;
+                Ok(())
+            })
+            .await?;
+        }
+
+        let payload =
+            self.payloads.get(url).cloned().ok_or_else(|| {
+                X509ClientError::Error(format!("no payload registered for {}", url))
+            })?;
+
+        if let Some(validators) = &options.validators {
+            if !validators.is_empty() && validators == &payload.validators {
+                return Ok(X509Payload {
+                    validators: payload.validators,
+                    not_modified: true,
+                    ..Default::default()
+                });
+            }
+        }
+
+        check_limit(payload.len(), limit)?;
+
+        Ok(payload)
+    }
+
+    async fn fetch_stream(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509StreamPayload> {
+        let limit = options.limit;
+        let mut payload = self
+            .fetch(
+                url,
+                &X509TransportOptions {
+                    limit: None,
+                    validators: None,
+                    ..options.clone()
+                },
+            )
+            .await?;
+
+        match self.chunk_size {
+            Some(chunk_size) if payload.documents.len() == 1 => {
+                let document = payload.documents.remove(0);
+                let chunks = document
+                    .bytes
+                    .chunks(chunk_size)
+                    .scan(0, |size, chunk| {
+                        *size += chunk.len();
+                        Some((Bytes::copy_from_slice(chunk), *size))
+                    })
+                    .collect::<Vec<_>>();
+                let chunks = stream::iter(chunks)
+                    .map(move |(chunk, size)| check_limit(size, limit).map(|_| chunk));
+                Ok(X509StreamPayload::Chunks(document.hint, chunks.boxed()))
+            }
+            _ => {
+                check_limit(payload.len(), limit)?;
+                Ok(X509StreamPayload::Complete(payload))
+            }
+        }
+    }
+}
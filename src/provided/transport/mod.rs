@@ -0,0 +1,116 @@
+mod data;
+mod file;
+mod http;
+#[cfg(feature = "ldap")]
+mod ldap;
+mod mock;
+#[cfg(feature = "tls")]
+mod starttls;
+#[cfg(feature = "tls")]
+mod tls;
+
+pub use data::*;
+pub use file::*;
+pub use http::*;
+#[cfg(feature = "ldap")]
+pub use ldap::*;
+pub use mock::*;
+#[cfg(feature = "tls")]
+pub use starttls::*;
+#[cfg(feature = "tls")]
+pub use tls::*;
+
+use crate::api::{X509Payload, X509StreamPayload, X509Transport, X509TransportOptions};
+use crate::{X509ClientConfiguration, X509ClientError, X509ClientResult};
+use std::future::Future;
+use std::time::Duration;
+use url::Url;
+
+/// Default transport. Dispatches `File` scheme to [`FileX509Transport`], `data` scheme to [`DataX509Transport`],
+/// `ldap` and `ldaps` schemes to `LdapX509Transport` if the `ldap` feature is enabled,
+/// `tls` and `<protocol>+starttls` schemes to `TlsX509Transport` and `StartTlsX509Transport` if the `tls` feature is enabled,
+/// all others to [`ReqwestX509Transport`].
+#[derive(Clone, Default)]
+pub struct DefaultX509Transport {
+    http: ReqwestX509Transport,
+    file: FileX509Transport,
+    data: DataX509Transport,
+    #[cfg(feature = "ldap")]
+    ldap: LdapX509Transport,
+    #[cfg(feature = "tls")]
+    tls: TlsX509Transport,
+    #[cfg(feature = "tls")]
+    starttls: StartTlsX509Transport,
+}
+
+impl DefaultX509Transport {
+    pub fn new(config: &X509ClientConfiguration) -> Self {
+        Self {
+            http: ReqwestX509Transport::with_configuration(config),
+            file: FileX509Transport::new(config.recursive).with_types(config.types.clone()),
+            data: DataX509Transport,
+            #[cfg(feature = "ldap")]
+            ldap: LdapX509Transport::new().with_egress(config.egress.clone()),
+            #[cfg(feature = "tls")]
+            tls: TlsX509Transport::new().with_egress(config.egress.clone()),
+            #[cfg(feature = "tls")]
+            starttls: StartTlsX509Transport::new().with_egress(config.egress.clone()),
+        }
+    }
+}
+
+impl X509Transport for DefaultX509Transport {
+    async fn fetch(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509Payload> {
+        match url.scheme() {
+            "file" => self.file.fetch(url, options).await,
+            "data" => self.data.fetch(url, options).await,
+            #[cfg(feature = "ldap")]
+            "ldap" | "ldaps" => self.ldap.fetch(url, options).await,
+            #[cfg(feature = "tls")]
+            "tls" => self.tls.fetch(url, options).await,
+            #[cfg(feature = "tls")]
+            scheme if StartTlsProtocol::from_scheme(scheme).is_some() => {
+                self.starttls.fetch(url, options).await
+            }
+            _ => self.http.fetch(url, options).await,
+        }
+    }
+
+    /// Streams `file` and http/s urls, other schemes are transferred complete
+    async fn fetch_stream(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509StreamPayload> {
+        match url.scheme() {
+            "file" => self.file.fetch_stream(url, options).await,
+            "http" | "https" => self.http.fetch_stream(url, options).await,
+            _ => Ok(X509StreamPayload::Complete(self.fetch(url, options).await?)),
+        }
+    }
+}
+
+/// Fail with [`X509ClientError::Timeout`] if `future` does not complete within `timeout`
+pub async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    future: impl Future<Output = X509ClientResult<T>>,
+) -> X509ClientResult<T> {
+    match timeout {
+        None => future.await,
+        Some(timeout) => tokio::time::timeout(timeout, future)
+            .await
+            .map_err(|_| X509ClientError::Timeout(timeout))?,
+    }
+}
+
+/// Fail with [`X509ClientError::LimitExceeded`] if `size` exceeds `limit`
+pub fn check_limit(size: usize, limit: Option<usize>) -> X509ClientResult<()> {
+    match limit {
+        Some(limit) if size > limit => Err(X509ClientError::LimitExceeded { size, limit }),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,226 @@
+use crate::api::{X509Payload, X509Transport, X509TransportOptions};
+use crate::provided::transport::tls::{connect, handshake, host_port, server_name};
+use crate::provided::transport::with_timeout;
+use crate::{X509ClientError, X509ClientResult, X509EgressPolicy};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use url::Url;
+
+/// Max length of a single protocol line
+const MAX_LINE: usize = 4096;
+/// Max number of lines in a single protocol response
+const MAX_LINES: usize = 128;
+
+/// LDAP StartTLS extended request, message id 1
+const LDAP_STARTTLS_REQUEST: &[u8] = b"\x30\x1d\x02\x01\x01\x77\x18\x80\x161.3.6.1.4.1.1466.20037";
+/// PostgreSQL SSLRequest, length 8 and request code 80877103
+const POSTGRES_SSL_REQUEST: &[u8] = &[0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f];
+
+/// Protocols supporting an upgrade to TLS
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StartTlsProtocol {
+    Smtp,
+    Imap,
+    Pop3,
+    Ldap,
+    Postgres,
+}
+
+impl StartTlsProtocol {
+    /// Protocol for a `<protocol>+starttls` scheme
+    pub fn from_scheme(scheme: &str) -> Option<Self> {
+        match scheme {
+            "smtp+starttls" => Some(Self::Smtp),
+            "imap+starttls" => Some(Self::Imap),
+            "pop3+starttls" => Some(Self::Pop3),
+            "ldap+starttls" => Some(Self::Ldap),
+            "postgres+starttls" | "postgresql+starttls" => Some(Self::Postgres),
+            _ => None,
+        }
+    }
+
+    /// Well-known plaintext port
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Self::Smtp => 25,
+            Self::Imap => 143,
+            Self::Pop3 => 110,
+            Self::Ldap => 389,
+            Self::Postgres => 5432,
+        }
+    }
+
+    /// Speak just enough of the protocol to request the upgrade to TLS
+    async fn upgrade(&self, stream: &mut TcpStream) -> X509ClientResult<()> {
+        match self {
+            Self::Smtp => {
+                expect(read_response(stream, smtp_last).await?, "220")?;
+                stream.write_all(b"EHLO x509-client\r\n").await?;
+                expect(read_response(stream, smtp_last).await?, "250")?;
+                stream.write_all(b"STARTTLS\r\n").await?;
+                expect(read_response(stream, smtp_last).await?, "220")
+            }
+            Self::Imap => {
+                expect(read_line(stream).await?, "* OK")?;
+                stream.write_all(b"a001 STARTTLS\r\n").await?;
+                expect(
+                    read_response(stream, |l| l.starts_with("a001 ")).await?,
+                    "a001 OK",
+                )
+            }
+            Self::Pop3 => {
+                expect(read_line(stream).await?, "+OK")?;
+                stream.write_all(b"STLS\r\n").await?;
+                expect(read_line(stream).await?, "+OK")
+            }
+            Self::Ldap => {
+                stream.write_all(LDAP_STARTTLS_REQUEST).await?;
+                ldap_extended_response(stream).await
+            }
+            Self::Postgres => {
+                stream.write_all(POSTGRES_SSL_REQUEST).await?;
+                match stream.read_u8().await? {
+                    b'S' => Ok(()),
+                    _ => Err(X509ClientError::Error(
+                        "postgres server refused ssl request".to_string(),
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// STARTTLS transport, e.g. `smtp+starttls://host:port?sni=name`.
+/// Supported schemes are `smtp+starttls`, `imap+starttls`, `pop3+starttls`, `ldap+starttls` and `postgres+starttls`.
+/// Upgrades the plaintext connection, then completes a TLS handshake without verifying the peer,
+/// returning the presented certificate chain.
+#[derive(Clone, Default)]
+pub struct StartTlsX509Transport {
+    egress: Option<X509EgressPolicy>,
+}
+
+impl StartTlsX509Transport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// If Some, connect to the addresses resolved and checked by the egress policy, the host is not resolved again
+    pub fn with_egress(mut self, egress: Option<X509EgressPolicy>) -> Self {
+        self.egress = egress;
+        self
+    }
+}
+
+impl X509Transport for StartTlsX509Transport {
+    async fn fetch(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509Payload> {
+        let limit = options.limit;
+        let protocol = StartTlsProtocol::from_scheme(url.scheme()).ok_or_else(|| {
+            X509ClientError::Error(format!("unsupported starttls scheme {}", url.scheme()))
+        })?;
+
+        let (host, port) = host_port(url, protocol.default_port())?;
+        let mut stream = with_timeout(
+            options.connect_timeout,
+            connect(&host, port, self.egress.as_ref()),
+        )
+        .await?;
+        with_timeout(options.read_timeout, protocol.upgrade(&mut stream)).await?;
+        with_timeout(
+            options.read_timeout,
+            handshake(stream, &server_name(url), limit),
+        )
+        .await
+    }
+}
+
+/// SMTP multiline responses use `-` after the reply code, the last line uses a space
+fn smtp_last(line: &str) -> bool {
+    line.as_bytes().get(3) != Some(&b'-')
+}
+
+fn expect(line: String, prefix: &str) -> X509ClientResult<()> {
+    if !line.starts_with(prefix) {
+        return Err(X509ClientError::Error(format!(
+            "unexpected starttls response: {}",
+            line
+        )));
+    }
+    Ok(())
+}
+
+/// Read lines until `last` matches, returning the last line
+async fn read_response<F: Fn(&str) -> bool>(
+    stream: &mut TcpStream,
+    last: F,
+) -> X509ClientResult<String> {
+    for _ in 0..MAX_LINES {
+        let line = read_line(stream).await?;
+        if last(&line) {
+            return Ok(line);
+        }
+    }
+    Err(X509ClientError::Error(
+        "starttls response exceeded max lines".to_string(),
+    ))
+}
+
+/// Read a single CRLF terminated line. Reads byte by byte, so no TLS data is consumed.
+async fn read_line(stream: &mut TcpStream) -> X509ClientResult<String> {
+    let mut line = vec![];
+    loop {
+        match stream.read_u8().await? {
+            b'\n' => break,
+            b => line.push(b),
+        }
+        if line.len() > MAX_LINE {
+            return Err(X509ClientError::Error(
+                "starttls response exceeded max line length".to_string(),
+            ));
+        }
+    }
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    Ok(String::from_utf8_lossy(&line).to_string())
+}
+
+/// Read the LDAP StartTLS extended response, expecting result code success
+async fn ldap_extended_response(stream: &mut TcpStream) -> X509ClientResult<()> {
+    let invalid = |e: &str| X509ClientError::Error(format!("ldap starttls failed: {}", e));
+
+    if stream.read_u8().await? != 0x30 {
+        return Err(invalid("invalid response"));
+    }
+    let len = match stream.read_u8().await? {
+        len if len < 0x80 => len as usize,
+        len if len & 0x7f <= 2 => {
+            let mut len_octets = vec![0u8; (len & 0x7f) as usize];
+            stream.read_exact(&mut len_octets).await?;
+            len_octets
+                .iter()
+                .fold(0usize, |acc, b| (acc << 8) | *b as usize)
+        }
+        _ => return Err(invalid("response too long")),
+    };
+    let mut message = vec![0u8; len];
+    stream.read_exact(&mut message).await?;
+
+    // messageID INTEGER, followed by ExtendedResponse [APPLICATION 24] { resultCode ENUMERATED, .. }
+    let id_len = *message.get(1).ok_or_else(|| invalid("invalid response"))? as usize;
+    let response = match message.get(2 + id_len..) {
+        Some([0x78, len, response @ ..]) if *len < 0x80 => response,
+        Some([0x78, len, response @ ..]) => response
+            .get((*len & 0x7f) as usize..)
+            .ok_or_else(|| invalid("invalid response"))?,
+        _ => return Err(invalid("invalid response")),
+    };
+    match response {
+        [0x0a, 0x01, 0, ..] => Ok(()),
+        [0x0a, 0x01, result_code, ..] => Err(invalid(&format!("result code {}", result_code))),
+        _ => Err(invalid("invalid response")),
+    }
+}
@@ -0,0 +1,145 @@
+use crate::api::{X509Document, X509Hint, X509Payload, X509Transport, X509TransportOptions};
+use crate::provided::transport::{check_limit, with_timeout};
+use crate::{X509ClientError, X509ClientResult, X509EgressPolicy};
+use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_openssl::SslStream;
+use url::Url;
+
+/// Default port if the `tls` URL does not specify one
+pub(crate) const TLS_DEFAULT_PORT: u16 = 443;
+
+/// `tls` scheme transport, e.g. `tls://host:port` or `tls://host:port?sni=name`.
+/// Completes a TLS handshake without verifying the peer, returning the presented certificate chain.
+/// The `sni` query parameter overrides the server name indication, which defaults to the URL host.
+#[derive(Clone, Default)]
+pub struct TlsX509Transport {
+    egress: Option<X509EgressPolicy>,
+}
+
+impl TlsX509Transport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// If Some, connect to the addresses resolved and checked by the egress policy, the host is not resolved again
+    pub fn with_egress(mut self, egress: Option<X509EgressPolicy>) -> Self {
+        self.egress = egress;
+        self
+    }
+}
+
+impl X509Transport for TlsX509Transport {
+    async fn fetch(
+        &self,
+        url: &Url,
+        options: &X509TransportOptions,
+    ) -> X509ClientResult<X509Payload> {
+        let limit = options.limit;
+        let (host, port) = host_port(url, TLS_DEFAULT_PORT)?;
+        let stream = with_timeout(
+            options.connect_timeout,
+            connect(&host, port, self.egress.as_ref()),
+        )
+        .await?;
+        with_timeout(
+            options.read_timeout,
+            handshake(stream, &server_name(url), limit),
+        )
+        .await
+    }
+}
+
+/// Host and port of `url`
+pub(crate) fn host_port(url: &Url, default_port: u16) -> X509ClientResult<(String, u16)> {
+    let host = match url.host() {
+        Some(url::Host::Ipv6(ip)) => ip.to_string(),
+        Some(host) => host.to_string(),
+        None => {
+            return Err(X509ClientError::UrlParseError(format!(
+                "missing host {}",
+                url
+            )))
+        }
+    };
+    Ok((host, url.port().unwrap_or(default_port)))
+}
+
+/// Connect to `host`, resolving it once with the egress policy if Some,
+/// so the connection is made to the checked addresses rather than to a second resolution of the host
+pub(crate) async fn connect(
+    host: &str,
+    port: u16,
+    egress: Option<&X509EgressPolicy>,
+) -> X509ClientResult<TcpStream> {
+    let egress = match egress {
+        Some(egress) => egress,
+        None => return Ok(TcpStream::connect((host, port)).await?),
+    };
+    let addrs = egress
+        .resolve(host)
+        .await?
+        .into_iter()
+        .map(|addr| SocketAddr::new(addr.ip(), port))
+        .collect::<Vec<_>>();
+    Ok(TcpStream::connect(addrs.as_slice()).await?)
+}
+
+/// Server name indication, the `sni` query parameter or the URL domain.
+/// Empty if the URL host is an IP address and no override is supplied.
+pub(crate) fn server_name(url: &Url) -> String {
+    url.query_pairs()
+        .find(|(k, _)| k == "sni")
+        .map(|(_, v)| v.to_string())
+        .or_else(|| url.domain().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Complete a TLS handshake over `stream` without verifying the peer, returning the presented certificate chain
+pub(crate) async fn handshake<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    server_name: &str,
+    limit: Option<usize>,
+) -> X509ClientResult<X509Payload> {
+    let mut connector = SslConnector::builder(SslMethod::tls_client())?;
+    connector.set_verify(SslVerifyMode::NONE);
+
+    let ssl = connector
+        .build()
+        .configure()?
+        .use_server_name_indication(!server_name.is_empty())
+        .verify_hostname(false)
+        .into_ssl(server_name)?;
+
+    let mut stream = SslStream::new(ssl, stream)?;
+    Pin::new(&mut stream).connect().await?;
+
+    let mut documents = vec![];
+    match stream.ssl().peer_cert_chain() {
+        Some(chain) => {
+            for certificate in chain {
+                documents.push(cer(certificate.to_der()?));
+            }
+        }
+        None => {
+            if let Some(certificate) = stream.ssl().peer_certificate() {
+                documents.push(cer(certificate.to_der()?));
+            }
+        }
+    }
+
+    let payload = X509Payload::from(documents);
+    check_limit(payload.len(), limit)?;
+
+    Ok(payload)
+}
+
+fn cer(src: Vec<u8>) -> X509Document {
+    X509Document::new(
+        X509Hint::ContentType("application/pkix-cert".to_string()),
+        src,
+    )
+}
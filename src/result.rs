@@ -1,20 +1,50 @@
 use std::convert::Infallible;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
+use std::time::Duration;
 use std::{io, result};
 
 use crate::api::X509IteratorError;
 use http::uri::InvalidUri;
-use url::ParseError;
+use url::{ParseError, Url};
 
 pub type X509ClientResult<T> = result::Result<T, X509ClientError>;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum X509ClientError {
     Error(String),
     IoError(io::Error),
     UrlParseError(String),
     ClientError(reqwest::Error),
+    /// Unsuccessful http `status`, along with the `Retry-After` http header if present
+    HttpStatus {
+        status: u16,
+        retry_after: Option<Duration>,
+    },
+    /// Payload `size` in bytes exceeded the configured transfer `limit`
+    LimitExceeded {
+        size: usize,
+        limit: usize,
+    },
+    /// Operation exceeded the configured timeout
+    Timeout(Duration),
+    /// Offline mode, the url is not available in the on-disk cache
+    Offline(String),
+    /// Every url failed, along with the failure of each url in the supplied order
+    AllFailed(Vec<(Url, X509ClientError)>),
+    /// Connection refused by the configured egress policy
+    EgressDenied(String),
+    /// Url scheme not permitted by the configured scheme policy
+    SchemeNotPermitted(String),
+    /// Redirect refused by the configured redirect restrictions
+    RedirectRefused(String),
+    /// Archive entry count exceeded the configured `limit`
+    EntryLimitExceeded {
+        limit: usize,
+    },
+    /// Failure of a transport protocol client, e.g. an LDAP or TLS error, regardless of the enabled features
+    Transport(Box<dyn Error + Send + Sync>),
     X509IteratorError(Box<dyn X509IteratorError>),
 }
 
@@ -25,13 +55,134 @@ impl Display for X509ClientError {
             X509ClientError::IoError(e) => write!(f, "x509-client -> io error: {}", e),
             X509ClientError::UrlParseError(e) => write!(f, "x509-client -> url parse error: {}", e),
             X509ClientError::ClientError(e) => write!(f, "x509-client -> http client error: {}", e),
+            X509ClientError::HttpStatus { status, .. } => {
+                write!(f, "x509-client -> http status {}", status)
+            }
+            X509ClientError::LimitExceeded { size, limit } => write!(
+                f,
+                "x509-client -> total transferred bytes {} exceeded limit {}",
+                size, limit
+            ),
+            X509ClientError::Timeout(timeout) => {
+                write!(f, "x509-client -> timed out after {:?}", timeout)
+            }
+            X509ClientError::Offline(url) => {
+                write!(f, "x509-client -> offline, no cached payload for {}", url)
+            }
+            X509ClientError::AllFailed(failures) => {
+                write!(f, "x509-client -> all urls failed")?;
+                for (url, e) in failures {
+                    write!(f, "; {}: {}", url, e)?;
+                }
+                Ok(())
+            }
+            X509ClientError::EgressDenied(e) => write!(f, "x509-client -> egress denied: {}", e),
+            X509ClientError::SchemeNotPermitted(scheme) => {
+                write!(f, "x509-client -> scheme not permitted: {}", scheme)
+            }
+            X509ClientError::RedirectRefused(e) => {
+                write!(f, "x509-client -> redirect refused: {}", e)
+            }
+            X509ClientError::EntryLimitExceeded { limit } => {
+                write!(f, "x509-client -> archive entries exceeded limit {}", limit)
+            }
+            X509ClientError::Transport(e) => write!(f, "x509-client -> transport error: {}", e),
             X509ClientError::X509IteratorError(e) => write!(f, "x509-client -> {}", e),
         }
     }
 }
 
+impl X509ClientError {
+    /// True for transient failures worth another attempt: timeouts, connection errors,
+    /// http status 5xx and 429, and the LDAP `busy` and `unavailable` results.
+    /// Other http statuses and LDAP results, deserialization failures and exceeded limits are permanent.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            X509ClientError::IoError(e) => is_retryable_io(e),
+            X509ClientError::ClientError(e) => match e.status() {
+                Some(status) => is_retryable_status(status.as_u16()),
+                None => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+            },
+            X509ClientError::HttpStatus { status, .. } => is_retryable_status(*status),
+            X509ClientError::Timeout(_) => true,
+            X509ClientError::Transport(e) => is_retryable_transport(e.as_ref()),
+            _ => false,
+        }
+    }
+
+    /// Delay requested by the server before another attempt, i.e. the `Retry-After` http header
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            X509ClientError::HttpStatus { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+fn is_retryable_status(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
+}
+
+fn is_retryable_io(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::TimedOut
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::Interrupted
+    )
+}
+
+fn is_retryable_transport(e: &(dyn Error + Send + Sync + 'static)) -> bool {
+    if let Some(e) = e.downcast_ref::<io::Error>() {
+        return is_retryable_io(e);
+    }
+    #[cfg(feature = "ldap")]
+    if let Some(e) = e.downcast_ref::<ldap3::LdapError>() {
+        return is_retryable_ldap(e);
+    }
+    #[cfg(feature = "tls")]
+    if let Some(e) = e.downcast_ref::<openssl::ssl::Error>() {
+        return e.io_error().is_some_and(is_retryable_io);
+    }
+    false
+}
+
+#[cfg(feature = "ldap")]
+fn is_retryable_ldap(e: &ldap3::LdapError) -> bool {
+    use ldap3::LdapError;
+    match e {
+        LdapError::Io { source } => is_retryable_io(source),
+        // connection closed while the operation was in flight
+        LdapError::OpSend { .. }
+        | LdapError::ResultRecv { .. }
+        | LdapError::IdScrubSend { .. }
+        | LdapError::EndOfStream
+        | LdapError::Timeout { .. } => true,
+        // busy, unavailable
+        LdapError::LdapResult { result } => matches!(result.rc, 51 | 52),
+        _ => false,
+    }
+}
+
 impl Error for X509ClientError {}
 
+/// Egress policy violation raised within reqwest, mapped to `X509ClientError::EgressDenied`
+#[derive(Debug)]
+pub(crate) struct X509EgressError(pub(crate) String);
+
+impl Display for X509EgressError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "egress denied: {}", self.0)
+    }
+}
+
+impl Error for X509EgressError {}
+
 impl X509IteratorError for X509ClientError {}
 
 impl From<io::Error> for X509ClientError {
@@ -54,10 +205,39 @@ impl From<ParseError> for X509ClientError {
 
 impl From<reqwest::Error> for X509ClientError {
     fn from(e: reqwest::Error) -> Self {
+        // egress policy violations raised by the resolver
+        let mut source = e.source();
+        while let Some(inner) = source {
+            if let Some(denied) = inner.downcast_ref::<X509EgressError>() {
+                return X509ClientError::EgressDenied(denied.0.clone());
+            }
+            source = inner.source();
+        }
         X509ClientError::ClientError(e)
     }
 }
 
+#[cfg(feature = "ldap")]
+impl From<ldap3::LdapError> for X509ClientError {
+    fn from(e: ldap3::LdapError) -> Self {
+        X509ClientError::Transport(Box::new(e))
+    }
+}
+
+#[cfg(feature = "tls")]
+impl From<openssl::ssl::Error> for X509ClientError {
+    fn from(e: openssl::ssl::Error) -> Self {
+        X509ClientError::Transport(Box::new(e))
+    }
+}
+
+#[cfg(feature = "tls")]
+impl From<openssl::error::ErrorStack> for X509ClientError {
+    fn from(e: openssl::error::ErrorStack) -> Self {
+        X509ClientError::Transport(Box::new(openssl::ssl::Error::from(e)))
+    }
+}
+
 impl From<Box<dyn X509IteratorError>> for X509ClientError {
     fn from(e: Box<dyn X509IteratorError>) -> Self {
         X509ClientError::X509IteratorError(e)
@@ -0,0 +1,148 @@
+use crate::der::tlv;
+use crate::parse::X509Type;
+
+const PEM_BEGIN: &[u8] = b"-----BEGIN ";
+const PEM_DASHES: &[u8] = b"-----";
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OID: u8 = 0x06;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+/// Context specific, constructed `[0]`
+const TAG_EXPLICIT_0: u8 = 0xa0;
+
+/// 1.2.840.113549.1.7.1, PKCS7 data
+const OID_DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01];
+/// 1.2.840.113549.1.7.2, PKCS7 signedData
+const OID_SIGNED_DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+
+/// Confidence of a sniffed type, ordered from `Low` to `High`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum X509Confidence {
+    /// Plausible, e.g. a DER sequence of unrecognised structure, or a PEM block of another label
+    Low,
+    /// Likely, e.g. a PEM certificate block following explanatory text
+    Medium,
+    /// Structure matches the type, e.g. a DER certificate or a PEM certificate block
+    High,
+}
+
+/// Type of a document determined from its bytes
+#[derive(Clone, Debug, PartialEq)]
+pub struct X509Sniff {
+    /// Sniffed type, `Crl` for certificate revocation lists and `Pkcs12` for PKCS12 keystores which cannot be deserialized
+    pub format: X509Type,
+    /// How conclusive the structure is
+    pub confidence: X509Confidence,
+}
+
+impl X509Sniff {
+    /// Determine the type from the PEM armor label or the DER structure, None if neither
+    pub fn sniff(src: &[u8]) -> Option<Self> {
+        Self::pem(src).or_else(|| Self::der(src))
+    }
+
+    fn new(format: X509Type, confidence: X509Confidence) -> Self {
+        Self { format, confidence }
+    }
+
+    fn pem(src: &[u8]) -> Option<Self> {
+        let text = src.trim_ascii_start();
+        if let Some(header) = text.strip_prefix(PEM_BEGIN) {
+            let line = &header[..header
+                .iter()
+                .position(|b| *b == b'\n')
+                .unwrap_or(header.len())];
+            let confidence = match find(line, PEM_DASHES).map(|end| &line[..end]) {
+                Some(b"CERTIFICATE" | b"X509 CERTIFICATE" | b"TRUSTED CERTIFICATE") => {
+                    X509Confidence::High
+                }
+                // another label, or an incomplete header
+                _ => X509Confidence::Low,
+            };
+            return Some(Self::new(X509Type::Pem, confidence));
+        }
+
+        // e.g. the text output of openssl preceding the block
+        find(src, b"-----BEGIN CERTIFICATE-----")
+            .map(|_| Self::new(X509Type::Pem, X509Confidence::Medium))
+    }
+
+    fn der(src: &[u8]) -> Option<Self> {
+        let (tag, content, rest) = tlv(src)?;
+        if tag != TAG_SEQUENCE {
+            return None;
+        }
+        let children = children(content)?;
+
+        let format = if is_pkcs12(&children) {
+            X509Type::Pkcs12
+        } else if is_pkcs7(&children) {
+            X509Type::Pkcs7
+        } else if let Some(format) = signed(&children) {
+            format
+        } else {
+            return Some(Self::new(X509Type::Cer, X509Confidence::Low));
+        };
+
+        // DER decoders reject trailing bytes
+        let confidence = match rest.is_empty() {
+            true => X509Confidence::High,
+            false => X509Confidence::Low,
+        };
+        Some(Self::new(format, confidence))
+    }
+}
+
+/// PFX: version 3, then the authSafe content info of type data or signedData
+fn is_pkcs12(children: &[(u8, &[u8])]) -> bool {
+    match children {
+        [(TAG_INTEGER, [3]), (TAG_SEQUENCE, auth_safe), ..] => matches!(
+            self::children(auth_safe).as_deref(),
+            Some([(TAG_OID, OID_DATA | OID_SIGNED_DATA), ..])
+        ),
+        _ => false,
+    }
+}
+
+/// ContentInfo: the signedData content type, then the explicitly tagged content
+fn is_pkcs7(children: &[(u8, &[u8])]) -> bool {
+    matches!(children, [(TAG_OID, OID_SIGNED_DATA), (TAG_EXPLICIT_0, _)])
+}
+
+/// Certificate or CRL: the signed tbsCertificate or tbsCertList, then the signature algorithm and the signature
+fn signed(children: &[(u8, &[u8])]) -> Option<X509Type> {
+    let tbs = match children {
+        [(TAG_SEQUENCE, tbs), (TAG_SEQUENCE, _), (TAG_BIT_STRING, _)] => self::children(tbs)?,
+        _ => return None,
+    };
+    let tags = tbs.iter().map(|(tag, _)| *tag).collect::<Vec<_>>();
+    match tags.as_slice() {
+        // version, serial number, signature, issuer, validity
+        [TAG_EXPLICIT_0, TAG_INTEGER, TAG_SEQUENCE, TAG_SEQUENCE, TAG_SEQUENCE, ..]
+        | [TAG_INTEGER, TAG_SEQUENCE, TAG_SEQUENCE, TAG_SEQUENCE, ..] => Some(X509Type::Cer),
+        // version, signature, issuer, thisUpdate
+        [TAG_INTEGER, TAG_SEQUENCE, TAG_SEQUENCE, TAG_UTC_TIME | TAG_GENERALIZED_TIME, ..]
+        | [TAG_SEQUENCE, TAG_SEQUENCE, TAG_UTC_TIME | TAG_GENERALIZED_TIME, ..] => {
+            Some(X509Type::Crl)
+        }
+        _ => None,
+    }
+}
+
+/// Tag and content of each element of `content`, None if malformed
+fn children(mut content: &[u8]) -> Option<Vec<(u8, &[u8])>> {
+    let mut children = vec![];
+    while !content.is_empty() {
+        let (tag, value, rest) = tlv(content)?;
+        children.push((tag, value));
+        content = rest;
+    }
+    Some(children)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
@@ -0,0 +1,207 @@
+use crate::api::{X509Document, X509Hint, X509Iterator};
+#[cfg(feature = "compression")]
+use crate::compression::is_compressed;
+use crate::parse::X509Type;
+use crate::sniff::{X509Confidence, X509Sniff};
+use crate::{X509ClientError, X509ClientResult};
+use bytes::Bytes;
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use std::collections::VecDeque;
+
+const PEM_BEGIN: &[u8] = b"-----BEGIN";
+const PEM_END: &[u8] = b"-----END";
+const PEM_DASHES: &[u8] = b"-----";
+
+/// How the body is decoded, determined once the first bytes are received
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Undetermined,
+    /// Each complete PEM block is deserialized as soon as received
+    Pem,
+    /// The whole body is buffered, then deserialized
+    Buffered,
+}
+
+struct Decoder<X: X509Iterator, F> {
+    strict: bool,
+    sniff: Option<X509Confidence>,
+    /// Type of the hint
+    hinted: X509Type,
+    hint: X509Hint,
+    chunks: BoxStream<'static, X509ClientResult<Bytes>>,
+    /// Deserialize the buffered body
+    deserialize: Option<F>,
+    mode: Mode,
+    buf: Vec<u8>,
+    pending: VecDeque<X::Item>,
+    done: bool,
+}
+
+/// Certificates of a chunked document, PEM blocks are deserialized as soon as complete.
+/// Other encodings, compressed bodies and archives cannot be deserialized incrementally,
+/// the whole body is buffered and deserialized by `deserialize` once received.
+pub(crate) fn certificates<X, F>(
+    strict: bool,
+    sniff: Option<X509Confidence>,
+    hinted: X509Type,
+    hint: X509Hint,
+    chunks: BoxStream<'static, X509ClientResult<Bytes>>,
+    deserialize: F,
+) -> impl Stream<Item = X509ClientResult<X::Item>>
+where
+    X: X509Iterator,
+    X509ClientError: From<X::X509IteratorError>,
+    F: FnOnce(X509Document) -> X509ClientResult<X>,
+{
+    let decoder = Decoder {
+        strict,
+        sniff,
+        hinted,
+        hint,
+        chunks,
+        deserialize: Some(deserialize),
+        mode: Mode::Undetermined,
+        buf: vec![],
+        pending: VecDeque::new(),
+        done: false,
+    };
+
+    stream::unfold(decoder, |mut decoder| async move {
+        match decoder.next().await {
+            Some(Ok(item)) => Some((Ok(item), decoder)),
+            Some(Err(e)) => {
+                // an error ends the stream
+                decoder.done = true;
+                decoder.pending.clear();
+                Some((Err(e), decoder))
+            }
+            None => None,
+        }
+    })
+}
+
+impl<X, F> Decoder<X, F>
+where
+    X: X509Iterator,
+    X509ClientError: From<X::X509IteratorError>,
+    F: FnOnce(X509Document) -> X509ClientResult<X>,
+{
+    async fn next(&mut self) -> Option<X509ClientResult<X::Item>> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(Ok(item));
+            }
+            if self.done {
+                return None;
+            }
+
+            let result = match self.chunks.next().await {
+                Some(Ok(chunk)) => self.receive(&chunk),
+                Some(Err(e)) => Err(e),
+                None => {
+                    self.done = true;
+                    self.finish()
+                }
+            };
+            if let Err(e) = result {
+                return Some(Err(e));
+            }
+        }
+    }
+
+    fn receive(&mut self, chunk: &[u8]) -> X509ClientResult<()> {
+        self.buf.extend_from_slice(chunk);
+
+        if self.mode == Mode::Undetermined {
+            self.mode = self.determine();
+        }
+
+        if self.mode == Mode::Pem {
+            while let Some(end) = pem_block_end(&self.buf) {
+                let block: Vec<u8> = self.buf.drain(..end).collect();
+                self.pending.extend(X::from_pem(block)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> X509ClientResult<()> {
+        let buf = std::mem::take(&mut self.buf);
+        match self.mode {
+            // trailing text after the last block, an "empty" PEM document is valid
+            Mode::Pem => {
+                if !buf.trim_ascii().is_empty() {
+                    self.pending.extend(X::from_pem(buf)?);
+                }
+            }
+            Mode::Undetermined | Mode::Buffered => {
+                if let Some(deserialize) = self.deserialize.take() {
+                    let document = X509Document::new(self.hint.clone(), buf);
+                    self.pending.extend(deserialize(document)?);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// PEM if sniffed with sufficient confidence or hinted in strict mode.
+    /// Otherwise PEM if the body starts with a PEM header and the hint is PEM or not conclusive.
+    fn determine(&self) -> Mode {
+        #[cfg(feature = "compression")]
+        if is_compressed(&self.buf) {
+            return Mode::Buffered;
+        }
+
+        let start = self.buf.trim_ascii_start();
+        if let Some(min) = self.sniff {
+            match X509Sniff::sniff(start) {
+                Some(sniffed) if sniffed.confidence >= min => {
+                    return match sniffed.format {
+                        X509Type::Pem => Mode::Pem,
+                        _ => Mode::Buffered,
+                    };
+                }
+                // too short to tell, or an incomplete PEM header
+                _ if (PEM_BEGIN.starts_with(start) || start.starts_with(PEM_BEGIN))
+                    && !start.contains(&b'\n') =>
+                {
+                    return Mode::Undetermined
+                }
+                _ => {}
+            }
+        }
+
+        match self.hinted {
+            X509Type::Pem if self.strict => Mode::Pem,
+            // in relaxed mode a misleading PEM hint must not prevent the fallback of a buffered parse
+            X509Type::Pem | X509Type::Unknown if !self.strict => {
+                if start.starts_with(PEM_BEGIN) {
+                    Mode::Pem
+                } else if PEM_BEGIN.starts_with(start) {
+                    // too short to tell
+                    Mode::Undetermined
+                } else {
+                    Mode::Buffered
+                }
+            }
+            _ => Mode::Buffered,
+        }
+    }
+}
+
+/// Offset past the end of the first complete PEM block, i.e. the line ending of its `-----END <label>-----` line
+fn pem_block_end(buf: &[u8]) -> Option<usize> {
+    let end = find(buf, PEM_END, 0)?;
+    let dashes = find(buf, PEM_DASHES, end + PEM_END.len())?;
+    let line = dashes + PEM_DASHES.len();
+    let newline = buf[line..].iter().position(|b| *b == b'\n')?;
+    Some(line + newline + 1)
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|position| from + position)
+}
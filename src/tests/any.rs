@@ -0,0 +1,104 @@
+use crate::api::{X509Hint, X509Payload};
+use crate::provided::debug::DebugX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::{X509Client, X509ClientConfiguration, X509ClientError};
+use bytes::Bytes;
+use std::time::Duration;
+use url::Url;
+
+fn url(path: &str) -> Url {
+    Url::parse("http://localhost").unwrap().join(path).unwrap()
+}
+
+fn client(
+    transport: &MockX509Transport,
+    stagger: Option<Duration>,
+) -> X509Client<DebugX509Iterator, MockX509Transport> {
+    X509Client::with_transport(
+        X509ClientConfiguration {
+            stagger,
+            ..Default::default()
+        },
+        transport.clone(),
+    )
+}
+
+#[tokio::test]
+async fn test_any_sequential() {
+    let transport = MockX509Transport::new()
+        .with_payload(url("b"), X509Payload::new(X509Hint::None, vec![2]))
+        .with_payload(url("c"), X509Payload::new(X509Hint::None, vec![3]));
+    let client = client(&transport, None);
+
+    let certificates = client
+        .get_any(&[url("a"), url("b"), url("c")])
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![Bytes::from(vec![2])],
+        certificates.into_iter().collect::<Vec<_>>()
+    );
+    assert_eq!(vec![url("a"), url("b")], transport.requests());
+}
+
+#[tokio::test]
+async fn test_any_failed() {
+    let transport = MockX509Transport::new();
+    let sequential = client(&transport, None);
+
+    match sequential.get_any(&[url("a"), url("b")]).await {
+        Err(X509ClientError::AllFailed(failures)) => {
+            assert_eq!(
+                vec![url("a"), url("b")],
+                failures.into_iter().map(|(url, _)| url).collect::<Vec<_>>()
+            );
+        }
+        _ => panic!("expected all failed"),
+    }
+
+    let racing = client(&transport, Some(Duration::from_millis(1)));
+    match racing.get_any(&[url("a"), url("b"), url("c")]).await {
+        Err(e @ X509ClientError::AllFailed(_)) => {
+            let e = e.to_string();
+            assert!(e.find("/a").unwrap() < e.find("/b").unwrap());
+            assert!(e.find("/b").unwrap() < e.find("/c").unwrap());
+        }
+        _ => panic!("expected all failed"),
+    }
+
+    assert!(racing.get_any(&[]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_any_race() {
+    // both urls are slow, the second url starts once the stagger elapsed
+    let slow = MockX509Transport::new()
+        .with_payload(url("a"), X509Payload::new(X509Hint::None, vec![1]))
+        .with_payload(url("b"), X509Payload::new(X509Hint::None, vec![2]))
+        .with_delay(Duration::from_millis(100));
+    let staggered = client(&slow, Some(Duration::from_millis(10)));
+
+    let certificates = staggered.get_any(&[url("a"), url("b")]).await.unwrap();
+    assert_eq!(
+        vec![Bytes::from(vec![1])],
+        certificates.into_iter().collect::<Vec<_>>()
+    );
+    assert_eq!(vec![url("a"), url("b")], slow.requests());
+
+    // a failure starts the next url without waiting for the stagger
+    let transport =
+        MockX509Transport::new().with_payload(url("b"), X509Payload::new(X509Hint::None, vec![2]));
+    let racing = client(&transport, Some(Duration::from_secs(60)));
+
+    let certificates = tokio::time::timeout(
+        Duration::from_secs(5),
+        racing.get_any(&[url("a"), url("b")]),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        vec![Bytes::from(vec![2])],
+        certificates.into_iter().collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,232 @@
+use crate::api::{X509Hint, X509Payload};
+use crate::provided::default::DefaultX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::{
+    X509ArchiveConfiguration, X509Client, X509ClientConfiguration, X509ClientError,
+    X509RequestOptions, X509Type,
+};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::StreamExt;
+use std::io::Write;
+use std::path::PathBuf;
+use url::Url;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+const CER: &[u8] = include_bytes!("../provided/default/tests/resource.resources.ciph.xxx.cer");
+const FULLCHAIN: &[u8] =
+    include_bytes!("../provided/default/tests/resource.resources.ciph.xxx-fullchain.pem");
+
+/// Two certificate files, a broken one and a file which is not recognised
+const ENTRIES: [(&str, &[u8]); 4] = [
+    ("certs/a.cer", CER),
+    ("certs/b.pem", FULLCHAIN),
+    ("certs/broken.p7c", b"broken"),
+    ("readme.txt", b"readme"),
+];
+
+fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(std::io::Cursor::new(vec![]));
+    writer
+        .add_directory("certs/", SimpleFileOptions::default())
+        .unwrap();
+    for (name, data) in entries {
+        writer
+            .start_file(*name, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(data).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+fn gzip(src: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(src).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn tar_gz(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+    for (name, data) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, name, *data).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+fn mock_client(
+    url: &Url,
+    payload: X509Payload,
+    config: X509ClientConfiguration,
+) -> X509Client<DefaultX509Iterator, MockX509Transport> {
+    X509Client::with_transport(
+        config,
+        MockX509Transport::new()
+            .with_payload(url.clone(), payload)
+            .with_chunk_size(256),
+    )
+}
+
+#[tokio::test]
+async fn test_archive_entries() {
+    let url = Url::parse("http://localhost/bundle").unwrap();
+
+    for (name, bytes) in [
+        ("bundle.zip", zip(&ENTRIES)),
+        ("bundle.tar.gz", tar_gz(&ENTRIES)),
+        ("bundle.tgz", tar_gz(&ENTRIES)),
+    ] {
+        for hint in [X509Hint::Path(PathBuf::from(name)), X509Hint::None] {
+            let client = mock_client(
+                &url,
+                X509Payload::new(hint, bytes.clone()),
+                X509ClientConfiguration::default(),
+            );
+            let response = client
+                .get_response(&url, &X509RequestOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(3, response.certificates.into_iter().len(), "{}", name);
+
+            let formats = response
+                .formats
+                .iter()
+                .map(|f| (f.path.clone().unwrap(), f.format.clone(), f.count))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                vec![
+                    (PathBuf::from("certs/a.cer"), X509Type::Cer, 1),
+                    (PathBuf::from("certs/b.pem"), X509Type::Pem, 2)
+                ],
+                formats,
+                "{}",
+                name
+            );
+            assert_eq!(
+                vec![PathBuf::from("certs/broken.p7c")],
+                response
+                    .errors
+                    .into_iter()
+                    .map(|e| e.path)
+                    .collect::<Vec<_>>(),
+                "{}",
+                name
+            );
+
+            assert_eq!(
+                3,
+                client
+                    .get_stream(&url)
+                    .filter(|c| futures::future::ready(c.is_ok()))
+                    .count()
+                    .await,
+                "{}",
+                name
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_archive_limits() {
+    let url = Url::parse("http://localhost/bundle.zip").unwrap();
+    let payload = X509Payload::new(X509Hint::None, zip(&ENTRIES));
+
+    let client = mock_client(
+        &url,
+        payload.clone(),
+        X509ClientConfiguration {
+            archive: X509ArchiveConfiguration {
+                max_entries: 4,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::EntryLimitExceeded { limit: 4 })
+    ));
+
+    let client = mock_client(
+        &url,
+        payload.clone(),
+        X509ClientConfiguration {
+            archive: X509ArchiveConfiguration {
+                max_size: CER.len() + FULLCHAIN.len(),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::LimitExceeded { .. })
+    ));
+
+    // a tar bomb, expanding beyond the transfer limit
+    let url = Url::parse("http://localhost/bundle.tar.gz").unwrap();
+    let zeros = vec![0; 1024 * 1024];
+    let bomb = tar_gz(&[("a.pem", &zeros)]);
+    let client = mock_client(
+        &url,
+        X509Payload::new(X509Hint::None, bomb.clone()),
+        X509ClientConfiguration {
+            limit: Some(bomb.len() * 2),
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::LimitExceeded { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_archive_bombs() {
+    let config = X509ClientConfiguration {
+        archive: X509ArchiveConfiguration {
+            max_size: 64 * 1024,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let zeros = vec![0; 1024 * 1024];
+
+    // the outer decompression is bounded, unrecognised entries included
+    let url = Url::parse("http://localhost/bundle.tar.gz").unwrap();
+    let bomb = tar_gz(&[("readme.txt", &zeros)]);
+    let client = mock_client(&url, X509Payload::new(X509Hint::None, bomb), config.clone());
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::LimitExceeded { limit: 65536, .. })
+    ));
+
+    // nested compressed entries are bounded
+    let url = Url::parse("http://localhost/bundle.zip").unwrap();
+    let fullchain = gzip(FULLCHAIN);
+    let client = mock_client(
+        &url,
+        X509Payload::new(X509Hint::None, zip(&[("ca.pem.gz", &fullchain)])),
+        config.clone(),
+    );
+    assert_eq!(2, client.get_all(&url).await.unwrap().into_iter().len());
+
+    let bomb = gzip(&zeros);
+    let client = mock_client(
+        &url,
+        X509Payload::new(
+            X509Hint::None,
+            zip(&[("ca.pem.gz", &fullchain), ("bomb.pem.gz", &bomb)]),
+        ),
+        config,
+    );
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::LimitExceeded { limit: 65536, .. })
+    ));
+}
@@ -0,0 +1,106 @@
+use crate::api::{X509Hint, X509Payload};
+use crate::provided::debug::DebugX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::{X509BatchConfiguration, X509Client, X509ClientConfiguration};
+use futures::StreamExt;
+use std::pin::pin;
+use std::time::Duration;
+use tokio::time::Instant;
+use url::Url;
+
+const DELAY: Duration = Duration::from_millis(50);
+
+fn urls(hosts: &[&str]) -> Vec<Url> {
+    hosts
+        .iter()
+        .enumerate()
+        .map(|(i, host)| Url::parse(&format!("http://{}/{}.cer", host, i)).unwrap())
+        .collect()
+}
+
+fn client(
+    urls: &[Url],
+    batch: X509BatchConfiguration,
+) -> X509Client<DebugX509Iterator, MockX509Transport> {
+    let transport = urls
+        .iter()
+        .fold(MockX509Transport::new(), |transport, url| {
+            transport.with_payload(url.clone(), X509Payload::new(X509Hint::None, vec![1]))
+        })
+        .with_delay(DELAY);
+    X509Client::with_transport(
+        X509ClientConfiguration {
+            batch,
+            ..Default::default()
+        },
+        transport,
+    )
+}
+
+#[tokio::test]
+async fn test_batch_results() {
+    let urls = urls(&["a", "b", "c", "d"]);
+    let client = client(&urls[..3], X509BatchConfiguration::default());
+
+    let results = client.get_batch(urls.clone()).await;
+    assert_eq!(
+        urls,
+        results
+            .iter()
+            .map(|(url, _)| url.clone())
+            .collect::<Vec<_>>()
+    );
+    assert!(results[..3].iter().all(|(_, result)| result.is_ok()));
+    assert!(results[3].1.is_err());
+
+    let results = client
+        .get_batch_stream(urls.clone())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(4, results.len());
+    assert_eq!(3, results.iter().filter(|(_, r)| r.is_ok()).count());
+}
+
+// the paused clock advances once every transfer is waiting, elapsed times are exact multiples of the delay
+#[tokio::test(start_paused = true)]
+async fn test_batch_concurrency() {
+    let urls = urls(&["a", "b", "c", "d", "e", "f"]);
+
+    let started = Instant::now();
+    let batch = X509BatchConfiguration {
+        concurrency: 6,
+        per_host: 1,
+    };
+    assert_eq!(6, client(&urls, batch).get_batch(urls.clone()).await.len());
+    assert_eq!(DELAY, started.elapsed());
+
+    let started = Instant::now();
+    let batch = X509BatchConfiguration {
+        concurrency: 2,
+        per_host: 1,
+    };
+    assert_eq!(6, client(&urls, batch).get_batch(urls.clone()).await.len());
+    assert_eq!(DELAY * 3, started.elapsed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_batch_per_host() {
+    let urls = urls(&["a", "a", "a", "a", "a", "a", "b"]);
+    let batch = X509BatchConfiguration {
+        concurrency: 16,
+        per_host: 2,
+    };
+    let client = client(&urls, batch);
+
+    let started = Instant::now();
+    let mut results = pin!(client.get_batch_stream(urls.clone()));
+
+    let mut completed = vec![];
+    while let Some((url, _)) = results.next().await {
+        completed.push(url);
+    }
+    assert_eq!(DELAY * 3, started.elapsed());
+
+    // the url of the idle host completes within the first wave, not queued behind the busy host
+    assert!(completed[..3].contains(&urls[6]));
+}
@@ -0,0 +1,50 @@
+use crate::api::{X509Hint, X509Payload};
+use crate::provided::debug::DebugX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::tests::http::{HttpResponse, HttpServer};
+use crate::{X509BlockingClient, X509ClientConfiguration, X509ClientError};
+use url::Url;
+
+#[test]
+fn test_blocking_http() {
+    // the server runs on its own runtime, the blocking client brings its own
+    let server_runtime = tokio::runtime::Runtime::new().unwrap();
+    let server =
+        server_runtime.block_on(HttpServer::start(|request| match request.path.as_str() {
+            "/ca.cer" => HttpResponse::new(200, &[1, 2, 3]),
+            _ => HttpResponse::new(404, b""),
+        }));
+
+    let client = X509BlockingClient::<DebugX509Iterator>::default();
+    assert_eq!(3, client.get(&server.url("/ca.cer")).unwrap().len());
+    assert!(matches!(
+        client.get(&server.url("/missing.cer")),
+        Err(X509ClientError::HttpStatus { status: 404, .. })
+    ));
+
+    let results = client.get_batch(vec![server.url("/ca.cer"), server.url("/missing.cer")]);
+    assert!(results[0].1.is_ok());
+    assert!(results[1].1.is_err());
+
+    let data = Url::parse("data:application/pkix-cert;base64,AQID").unwrap();
+    assert_eq!(3, client.get(&data).unwrap().len());
+}
+
+#[test]
+fn test_blocking_transport() {
+    let url = Url::parse("http://localhost/ca.cer").unwrap();
+    let transport = MockX509Transport::new()
+        .with_payload(url.clone(), X509Payload::new(X509Hint::None, vec![1, 2]));
+    let client = X509BlockingClient::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration::default(),
+        transport.clone(),
+    );
+
+    assert_eq!(2, client.get(&url).unwrap().len());
+    assert!(client
+        .get_any(&[
+            Url::parse("http://localhost/missing.cer").unwrap(),
+            url.clone()
+        ])
+        .is_ok());
+}
@@ -0,0 +1,177 @@
+use crate::api::{X509Freshness, X509Hint, X509Payload};
+use crate::client::X509CacheConfiguration;
+use crate::provided::debug::DebugX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::tests::http::{HttpResponse, HttpServer};
+use crate::{X509Client, X509ClientConfiguration};
+use std::time::Duration;
+use url::Url;
+
+fn url(path: &str) -> Url {
+    Url::parse("http://localhost").unwrap().join(path).unwrap()
+}
+
+fn payload(freshness: X509Freshness) -> X509Payload {
+    X509Payload {
+        freshness,
+        ..X509Payload::new(X509Hint::None, vec![1, 2, 3])
+    }
+}
+
+fn client(
+    transport: &MockX509Transport,
+    cache: X509CacheConfiguration,
+) -> X509Client<DebugX509Iterator, MockX509Transport> {
+    X509Client::with_transport(
+        X509ClientConfiguration {
+            cache: Some(cache),
+            ..Default::default()
+        },
+        transport.clone(),
+    )
+}
+
+#[tokio::test]
+async fn test_cache_invalidate() {
+    let transport = MockX509Transport::new()
+        .with_payload(url("a"), payload(X509Freshness::Unspecified))
+        .with_payload(url("b"), payload(X509Freshness::Unspecified));
+    let client = client(&transport, X509CacheConfiguration::default());
+
+    assert!(client.get(&url("a")).await.is_ok());
+    assert!(client.get(&url("a")).await.is_ok());
+    assert!(client.get(&url("b")).await.is_ok());
+    assert_eq!(vec![url("a"), url("b")], transport.requests());
+
+    client.invalidate(&url("a")).await.unwrap();
+    assert!(client.get(&url("a")).await.is_ok());
+    assert!(client.get(&url("b")).await.is_ok());
+    assert_eq!(vec![url("a"), url("b"), url("a")], transport.requests());
+
+    client.invalidate_all().await.unwrap();
+    assert!(client.get(&url("a")).await.is_ok());
+    assert!(client.get(&url("b")).await.is_ok());
+    assert_eq!(5, transport.requests().len());
+}
+
+#[tokio::test]
+async fn test_cache_lru() {
+    let transport = MockX509Transport::new()
+        .with_payload(url("a"), payload(X509Freshness::Unspecified))
+        .with_payload(url("b"), payload(X509Freshness::Unspecified))
+        .with_payload(url("c"), payload(X509Freshness::Unspecified));
+    let client = client(
+        &transport,
+        X509CacheConfiguration {
+            capacity: 2,
+            ..Default::default()
+        },
+    );
+
+    for path in ["a", "b", "a", "c", "a", "b"] {
+        assert!(client.get(&url(path)).await.is_ok());
+    }
+    assert_eq!(
+        vec![url("a"), url("b"), url("c"), url("b")],
+        transport.requests()
+    );
+}
+
+#[tokio::test]
+async fn test_cache_freshness() {
+    let transport = MockX509Transport::new()
+        .with_payload(url("no-store"), payload(X509Freshness::NoStore))
+        .with_payload(
+            url("expired"),
+            payload(X509Freshness::MaxAge(Duration::ZERO)),
+        )
+        .with_payload(
+            url("short"),
+            payload(X509Freshness::MaxAge(Duration::from_millis(50))),
+        )
+        .with_payload(url("ttl"), payload(X509Freshness::Unspecified));
+    let client = client(
+        &transport,
+        X509CacheConfiguration {
+            ttl: Duration::from_secs(60),
+            ..Default::default()
+        },
+    );
+
+    for path in ["no-store", "expired", "short", "ttl"] {
+        assert!(client.get(&url(path)).await.is_ok());
+        assert!(client.get(&url(path)).await.is_ok());
+    }
+    assert_eq!(
+        vec![
+            url("no-store"),
+            url("no-store"),
+            url("expired"),
+            url("expired"),
+            url("short"),
+            url("ttl")
+        ],
+        transport.requests()
+    );
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(client.get(&url("short")).await.is_ok());
+    assert!(client.get(&url("ttl")).await.is_ok());
+    assert_eq!(7, transport.requests().len());
+}
+
+#[tokio::test]
+async fn test_cache_http_headers() {
+    let server = HttpServer::start(|request| {
+        let response = HttpResponse::new(200, &[1, 2, 3]);
+        match request.path.as_str() {
+            "/max-age" => response.with_header("Cache-Control", "public, max-age=60"),
+            "/no-store" => response.with_header("Cache-Control", "max-age=60, no-store"),
+            "/no-cache" => response.with_header("Cache-Control", "no-cache, max-age=60"),
+            "/expires" => response
+                .with_header("Date", "Wed, 21 Oct 2015 07:28:00 GMT")
+                .with_header("Expires", "Wed, 21 Oct 2015 08:28:00 GMT"),
+            "/expired" => response.with_header("Expires", "Wed, 21 Oct 2015 07:28:00 GMT"),
+            "/invalid" => response.with_header("Expires", "0"),
+            _ => response,
+        }
+    })
+    .await;
+
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
+        cache: Some(X509CacheConfiguration {
+            ttl: Duration::ZERO,
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+
+    let paths = [
+        "/max-age",
+        "/no-store",
+        "/no-cache",
+        "/expires",
+        "/expired",
+        "/invalid",
+        "/unspecified",
+    ];
+    for path in paths {
+        assert!(client.get(&server.url(path)).await.is_ok());
+        assert!(client.get(&server.url(path)).await.is_ok());
+    }
+
+    let count = |path: &str| {
+        server
+            .requests()
+            .iter()
+            .filter(|request| request.path == path)
+            .count()
+    };
+    assert_eq!(1, count("/max-age"));
+    assert_eq!(2, count("/no-store"));
+    assert_eq!(2, count("/no-cache"));
+    assert_eq!(1, count("/expires"));
+    assert_eq!(2, count("/expired"));
+    assert_eq!(2, count("/invalid"));
+    assert_eq!(2, count("/unspecified"));
+}
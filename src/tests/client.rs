@@ -1,8 +1,9 @@
-use crate::client::{TestClient, X509ClientConfiguration};
+use crate::api::{X509Hint, X509Payload};
+use crate::client::X509ClientConfiguration;
 use crate::provided::debug::DebugX509Iterator;
-use crate::X509Client;
-use http::header::CONTENT_TYPE;
-use http::{HeaderMap, HeaderValue};
+use crate::provided::transport::MockX509Transport;
+use crate::tests::http::{HttpResponse, HttpServer};
+use crate::{X509Client, X509ClientError, X509SchemePolicy};
 use std::fs;
 use std::path::Path;
 use url::Url;
@@ -27,9 +28,9 @@ async fn test_client_file() {
 
     let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
         strict: true,
-        files: true,
+        schemes: X509SchemePolicy::Any,
         limit: None,
-        test_client: Default::default(),
+        ..Default::default()
     });
 
     assert!(client.get_all(&certificate_file).await.is_ok());
@@ -37,27 +38,27 @@ async fn test_client_file() {
 
     let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        test_client: Default::default(),
+        ..Default::default()
     });
 
     assert!(client.get_all(&certificate_file).await.is_err());
 
     let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
         strict: false,
-        files: true,
+        schemes: X509SchemePolicy::Any,
         limit: None,
-        test_client: Default::default(),
+        ..Default::default()
     });
 
     assert!(client.get_all(&certificate_file_unknown).await.is_ok());
 
     let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
         strict: false,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        test_client: Default::default(),
+        ..Default::default()
     });
 
     assert!(client.get_all(&certificate_file_unknown).await.is_err());
@@ -65,57 +66,158 @@ async fn test_client_file() {
 
 #[tokio::test]
 async fn test_client_http() {
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        CONTENT_TYPE,
-        HeaderValue::from_str("application/pkix-cert").unwrap(),
+    let url = Url::parse("http://localhost").unwrap();
+
+    let transport = MockX509Transport::new().with_payload(
+        url.clone(),
+        X509Payload::new(
+            X509Hint::ContentType("application/pkix-cert".to_string()),
+            vec![],
+        ),
     );
 
-    let mut headers_unknown_type = HeaderMap::new();
-    headers_unknown_type.insert(CONTENT_TYPE, HeaderValue::from_str("?/?").unwrap());
+    let transport_unknown_type = MockX509Transport::new().with_payload(
+        url.clone(),
+        X509Payload::new(X509Hint::ContentType("?/?".to_string()), vec![]),
+    );
 
-    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
-        strict: true,
-        files: false,
-        limit: None,
-        test_client: TestClient {
-            headers: headers.clone(),
-            bytes: Default::default(),
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            strict: true,
+            schemes: X509SchemePolicy::default(),
+            limit: None,
+            ..Default::default()
         },
-    });
+        transport,
+    );
 
-    assert!(client
-        .get_all(&Url::parse("http://localhost").unwrap())
-        .await
-        .is_ok());
+    assert!(client.get_all(&url).await.is_ok());
 
-    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
-        strict: true,
-        files: false,
-        limit: None,
-        test_client: TestClient {
-            headers: headers_unknown_type.clone(),
-            bytes: Default::default(),
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            strict: true,
+            schemes: X509SchemePolicy::default(),
+            limit: None,
+            ..Default::default()
         },
-    });
+        transport_unknown_type.clone(),
+    );
 
-    assert!(client
-        .get_all(&Url::parse("http://localhost").unwrap())
-        .await
-        .is_err());
+    assert!(client.get_all(&url).await.is_err());
 
-    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
-        strict: false,
-        files: false,
-        limit: None,
-        test_client: TestClient {
-            headers: headers_unknown_type.clone(),
-            bytes: Default::default(),
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            strict: false,
+            schemes: X509SchemePolicy::default(),
+            limit: None,
+            ..Default::default()
+        },
+        transport_unknown_type,
+    );
+
+    assert!(client.get_all(&url).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_client_mock() {
+    let url = Url::parse("http://localhost/ca.cer").unwrap();
+    let url_missing = Url::parse("http://localhost/missing.cer").unwrap();
+
+    let transport = MockX509Transport::new()
+        .with_payload(url.clone(), X509Payload::new(X509Hint::None, vec![0u8; 16]));
+
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            strict: false,
+            schemes: X509SchemePolicy::default(),
+            limit: Some(8),
+            ..Default::default()
+        },
+        transport.clone(),
+    );
+
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::LimitExceeded { size: 16, limit: 8 })
+    ));
+    assert!(client.get_all(&url_missing).await.is_err());
+
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            strict: false,
+            schemes: X509SchemePolicy::default(),
+            limit: Some(16),
+            ..Default::default()
         },
+        transport.clone(),
+    );
+
+    assert_eq!(16, client.get(&url).await.unwrap().len());
+    assert_eq!(
+        vec![url.clone(), url_missing, url.clone()],
+        transport.requests()
+    );
+}
+
+#[tokio::test]
+async fn test_client_schemes() {
+    let url = Url::parse("http://localhost/ca.cer").unwrap();
+    let transport = MockX509Transport::new()
+        .with_payload(url.clone(), X509Payload::new(X509Hint::None, vec![1]));
+
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            schemes: X509SchemePolicy::https(),
+            ..Default::default()
+        },
+        transport.clone(),
+    );
+
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::SchemeNotPermitted(scheme)) if scheme == "http"
+    ));
+    assert!(transport.requests().is_empty());
+
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            schemes: X509SchemePolicy::http(),
+            ..Default::default()
+        },
+        transport.clone(),
+    );
+
+    assert!(client.get_all(&url).await.is_ok());
+    assert!(matches!(
+        client.get_all(&Url::parse("data:,ca").unwrap()).await,
+        Err(X509ClientError::SchemeNotPermitted(_))
+    ));
+
+    assert!(X509SchemePolicy::Allow(vec!["LDAP".to_string()]).permits("ldap"));
+    assert!(!X509SchemePolicy::Deny(vec!["http".to_string()]).permits("http"));
+    assert!(X509SchemePolicy::Any.permits("file"));
+    assert!(!X509SchemePolicy::default().permits("file"));
+}
+
+#[tokio::test]
+async fn test_client_schemes_redirect() {
+    let server = HttpServer::start(|request| match request.path.as_str() {
+        "/redirect" => HttpResponse::new(302, b"").with_header(
+            "Location",
+            &format!("https://{}/ca.cer", request.headers["host"]),
+        ),
+        _ => HttpResponse::new(200, &[1, 2, 3]),
+    })
+    .await;
+
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
+        schemes: X509SchemePolicy::Allow(vec!["http".to_string()]),
+        ..Default::default()
     });
 
-    assert!(client
-        .get_all(&Url::parse("http://localhost").unwrap())
-        .await
-        .is_ok());
+    assert!(client.get(&server.url("/ca.cer")).await.is_ok());
+    assert!(matches!(
+        client.get(&server.url("/redirect")).await,
+        Err(X509ClientError::SchemeNotPermitted(scheme)) if scheme == "https"
+    ));
 }
@@ -0,0 +1,177 @@
+use crate::api::{X509Hint, X509Payload};
+use crate::provided::default::DefaultX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::{
+    X509Client, X509ClientConfiguration, X509ClientError, X509CompressionConfiguration,
+    X509RequestOptions, X509SchemePolicy, X509Type,
+};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use futures::StreamExt;
+use std::io::Write;
+use std::path::PathBuf;
+use url::Url;
+
+const FULLCHAIN: &[u8] =
+    include_bytes!("../provided/default/tests/resource.resources.ciph.xxx-fullchain.pem");
+const FULLCHAIN_BZ2: &[u8] =
+    include_bytes!("../provided/default/tests/resource.resources.ciph.xxx-fullchain.pem.bz2");
+
+fn gzip(src: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(src).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn zlib(src: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(src).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn zstd(src: &[u8]) -> Vec<u8> {
+    ruzstd::encoding::compress_to_vec(src, ruzstd::encoding::CompressionLevel::Fastest)
+}
+
+fn mock_client(
+    url: &Url,
+    payload: X509Payload,
+    limit: Option<usize>,
+) -> X509Client<DefaultX509Iterator, MockX509Transport> {
+    X509Client::with_transport(
+        X509ClientConfiguration {
+            limit,
+            ..Default::default()
+        },
+        MockX509Transport::new()
+            .with_payload(url.clone(), payload)
+            .with_chunk_size(64),
+    )
+}
+
+#[tokio::test]
+async fn test_compression_formats() {
+    let url = Url::parse("http://localhost/fullchain").unwrap();
+
+    for (name, bytes) in [
+        ("fullchain.pem.gz", gzip(FULLCHAIN)),
+        ("fullchain.pem.zz", zlib(FULLCHAIN)),
+        ("fullchain.pem.zst", zstd(FULLCHAIN)),
+        ("fullchain.pem.bz2", FULLCHAIN_BZ2.to_vec()),
+    ] {
+        // detected by filename extension and magic bytes
+        let client = mock_client(
+            &url,
+            X509Payload::new(X509Hint::Path(PathBuf::from(name)), bytes.clone()),
+            None,
+        );
+        let response = client
+            .get_response(&url, &X509RequestOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(2, response.certificates.into_iter().len(), "{}", name);
+        assert_eq!(X509Type::Pem, response.formats[0].hint, "{}", name);
+        assert!(!response.formats[0].is_fallback(), "{}", name);
+
+        // detected by magic bytes
+        let client = mock_client(&url, X509Payload::new(X509Hint::None, bytes), None);
+        assert_eq!(
+            2,
+            client.get_all(&url).await.unwrap().into_iter().len(),
+            "{}",
+            name
+        );
+        assert_eq!(2, client.get_stream(&url).count().await, "{}", name);
+    }
+}
+
+#[tokio::test]
+async fn test_compression_limit() {
+    let url = Url::parse("http://localhost/bomb.pem.gz").unwrap();
+    let bomb = gzip(&vec![b' '; 1024 * 1024]);
+    assert!(bomb.len() < 4096);
+
+    let client = mock_client(&url, X509Payload::new(X509Hint::None, bomb), Some(4096));
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::LimitExceeded { limit: 4096, .. })
+    ));
+    assert!(matches!(
+        Box::pin(client.get_stream(&url)).next().await,
+        Some(Err(X509ClientError::LimitExceeded { limit: 4096, .. }))
+    ));
+}
+
+#[tokio::test]
+async fn test_compression_max_size() {
+    let url = Url::parse("http://localhost/bomb.pem.gz").unwrap();
+
+    // no transfer limit, bounded by the default max size
+    let bomb = gzip(&vec![b' '; 17 * 1024 * 1024]);
+    let client = mock_client(&url, X509Payload::new(X509Hint::None, bomb.clone()), None);
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::LimitExceeded { limit, .. }) if limit == 16 * 1024 * 1024
+    ));
+
+    let client = X509Client::<DefaultX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            compression: X509CompressionConfiguration { max_size: 4096 },
+            ..Default::default()
+        },
+        MockX509Transport::new().with_payload(url.clone(), X509Payload::new(X509Hint::None, bomb)),
+    );
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::LimitExceeded { limit: 4096, .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_compression_invalid() {
+    let url = Url::parse("http://localhost/fullchain").unwrap();
+
+    // magic bytes only, parsed as is
+    let client = mock_client(
+        &url,
+        X509Payload::new(X509Hint::None, [&[0x1f, 0x8b], FULLCHAIN].concat()),
+        None,
+    );
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::Error(e)) if e == "failed to parse"
+    ));
+
+    // filename extension
+    let client = mock_client(
+        &url,
+        X509Payload::new(X509Hint::Path(PathBuf::from("fullchain.pem.gz")), FULLCHAIN),
+        None,
+    );
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::Error(e)) if e.starts_with("cannot decompress")
+    ));
+}
+
+#[tokio::test]
+async fn test_compression_directory() {
+    let dir = std::env::temp_dir().join(format!("x509-client-compression-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("fullchain.pem.gz"), gzip(FULLCHAIN)).unwrap();
+    std::fs::write(dir.join("readme.txt.gz"), gzip(b"readme")).unwrap();
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        schemes: X509SchemePolicy::Any,
+        ..Default::default()
+    });
+    let (certificates, errors) = client
+        .get_all_with_errors(&Url::from_directory_path(&dir).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(2, certificates.into_iter().len());
+    assert!(errors.is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
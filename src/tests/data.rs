@@ -0,0 +1,78 @@
+use crate::provided::debug::DebugX509Iterator;
+use crate::{X509Client, X509ClientConfiguration, X509SchemePolicy};
+use url::Url;
+
+#[tokio::test]
+async fn test_data() {
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::default(),
+        limit: None,
+        ..Default::default()
+    });
+
+    let url = Url::parse("data:application/pkix-cert;base64,AQID").unwrap();
+    assert_eq!(&[1u8, 2, 3][..], client.get(&url).await.unwrap());
+
+    let url = Url::parse("data:application/pkix-cert,%01%02%03").unwrap();
+    assert_eq!(&[1u8, 2, 3][..], client.get(&url).await.unwrap());
+
+    let url = Url::parse("data:,%01%02%03").unwrap();
+    assert!(client.get_all(&url).await.is_err());
+
+    let url = Url::parse("data:application/pkix-cert;base64,A").unwrap();
+    assert!(client.get_all(&url).await.is_err());
+
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
+        strict: false,
+        schemes: X509SchemePolicy::default(),
+        limit: Some(2),
+        ..Default::default()
+    });
+
+    let url = Url::parse("data:,%01%02").unwrap();
+    assert!(client.get_all(&url).await.is_ok());
+
+    let url = Url::parse("data:,%01%02%03").unwrap();
+    assert!(client.get_all(&url).await.is_err());
+}
+
+#[cfg(feature = "default")]
+#[tokio::test]
+async fn test_data_default() {
+    use crate::provided::default::DefaultX509Iterator;
+    use std::path::Path;
+
+    let resources = Path::new(file!())
+        .parent()
+        .unwrap()
+        .join("../provided/default/tests");
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::default(),
+        limit: None,
+        ..Default::default()
+    });
+
+    let cer = std::fs::read(resources.join("resource.resources.ciph.xxx.cer")).unwrap();
+    let url = Url::parse(&format!(
+        "data:application/pkix-cert,{}",
+        percent_encode(&cer)
+    ))
+    .unwrap();
+    assert_eq!(1, client.get_all(&url).await.unwrap().into_iter().len());
+
+    let pem = std::fs::read(resources.join("resource.resources.ciph.xxx-fullchain.pem")).unwrap();
+    let url = Url::parse(&format!(
+        "data:application/pem-certificate-chain,{}",
+        percent_encode(&pem)
+    ))
+    .unwrap();
+    assert_eq!(2, client.get_all(&url).await.unwrap().into_iter().len());
+}
+
+#[cfg(feature = "default")]
+fn percent_encode(src: &[u8]) -> String {
+    src.iter().map(|b| format!("%{:02X}", b)).collect()
+}
@@ -0,0 +1,325 @@
+use crate::api::{X509Freshness, X509Hint, X509Payload, X509Validators};
+use crate::client::X509DiskCacheConfiguration;
+use crate::provided::debug::DebugX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::{X509Client, X509ClientConfiguration, X509ClientError};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+fn url(path: &str) -> Url {
+    Url::parse("http://localhost").unwrap().join(path).unwrap()
+}
+
+fn payload(len: usize, freshness: X509Freshness) -> X509Payload {
+    X509Payload {
+        freshness,
+        ..X509Payload::new(
+            X509Hint::ContentType("application/pkix-cert".to_string()),
+            vec![1; len],
+        )
+    }
+}
+
+fn directory(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "x509-client-disk-cache-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn client(
+    transport: &MockX509Transport,
+    disk_cache: X509DiskCacheConfiguration,
+    revalidate: bool,
+) -> X509Client<DebugX509Iterator, MockX509Transport> {
+    X509Client::with_transport(
+        X509ClientConfiguration {
+            revalidate,
+            disk_cache: Some(disk_cache),
+            ..Default::default()
+        },
+        transport.clone(),
+    )
+}
+
+#[tokio::test]
+async fn test_disk_cache_offline() {
+    let directory = directory("offline");
+    let config = X509DiskCacheConfiguration {
+        directory: directory.clone(),
+        ..Default::default()
+    };
+
+    let transport = MockX509Transport::new()
+        .with_payload(url("a"), payload(3, X509Freshness::Unspecified))
+        .with_payload(url("no-store"), payload(3, X509Freshness::NoStore))
+        .with_payload(
+            url("short"),
+            payload(3, X509Freshness::MaxAge(Duration::from_millis(50))),
+        );
+    let online = client(&transport, config.clone(), false);
+    for path in ["a", "a", "no-store", "short"] {
+        assert!(online.get(&url(path)).await.is_ok());
+    }
+    assert_eq!(
+        vec![url("a"), url("no-store"), url("short")],
+        transport.requests()
+    );
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(online.get(&url("short")).await.is_ok());
+    assert_eq!(4, transport.requests().len());
+
+    // a new client, e.g. after a restart, never touching the transport
+    let transport = MockX509Transport::new();
+    let offline = client(
+        &transport,
+        X509DiskCacheConfiguration {
+            offline: true,
+            ..config
+        },
+        false,
+    );
+
+    assert_eq!(
+        Bytes::from(vec![1; 3]),
+        offline.get(&url("a")).await.unwrap()
+    );
+    assert!(offline.get(&url("short")).await.is_ok());
+    assert!(matches!(
+        offline.get(&url("no-store")).await,
+        Err(X509ClientError::Offline(_))
+    ));
+    assert!(transport.requests().is_empty());
+
+    offline.invalidate(&url("a")).await.unwrap();
+    assert!(matches!(
+        offline.get(&url("a")).await,
+        Err(X509ClientError::Offline(_))
+    ));
+
+    offline.invalidate_all().await.unwrap();
+    assert!(offline.get(&url("short")).await.is_err());
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[tokio::test]
+async fn test_disk_cache_capacity() {
+    let directory = directory("capacity");
+    let config = X509DiskCacheConfiguration {
+        directory: directory.clone(),
+        capacity: 2500,
+        ..Default::default()
+    };
+
+    let transport = MockX509Transport::new()
+        .with_payload(url("a"), payload(1000, X509Freshness::Unspecified))
+        .with_payload(url("b"), payload(1000, X509Freshness::Unspecified))
+        .with_payload(url("c"), payload(1000, X509Freshness::Unspecified))
+        .with_payload(url("large"), payload(3000, X509Freshness::Unspecified));
+    let online = client(&transport, config.clone(), false);
+    for path in ["a", "b", "a", "c", "large"] {
+        assert!(online.get(&url(path)).await.is_ok());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(4, transport.requests().len());
+
+    let offline = client(
+        &MockX509Transport::new(),
+        X509DiskCacheConfiguration {
+            offline: true,
+            ..config
+        },
+        false,
+    );
+    assert!(offline.get(&url("a")).await.is_ok());
+    assert!(offline.get(&url("b")).await.is_err());
+    assert!(offline.get(&url("c")).await.is_ok());
+    assert!(offline.get(&url("large")).await.is_err());
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[tokio::test]
+async fn test_disk_cache_revalidate() {
+    let directory = directory("revalidate");
+    let config = X509DiskCacheConfiguration {
+        directory: directory.clone(),
+        ..Default::default()
+    };
+
+    let transport = MockX509Transport::new().with_payload(
+        url("a"),
+        X509Payload {
+            validators: X509Validators {
+                etag: Some("\"v1\"".to_string()),
+                last_modified: None,
+            },
+            ..payload(3, X509Freshness::MaxAge(Duration::from_millis(50)))
+        },
+    );
+    assert!(client(&transport, config.clone(), false)
+        .get(&url("a"))
+        .await
+        .is_ok());
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // the expired entry supplies the validators, the transport answers not modified
+    let transport = MockX509Transport::new().with_payload(
+        url("a"),
+        X509Payload {
+            validators: X509Validators {
+                etag: Some("\"v1\"".to_string()),
+                last_modified: None,
+            },
+            ..payload(0, X509Freshness::Unspecified)
+        },
+    );
+    assert_eq!(
+        Bytes::from(vec![1; 3]),
+        client(&transport, config, true)
+            .get(&url("a"))
+            .await
+            .unwrap()
+    );
+    assert_eq!(vec![url("a")], transport.requests());
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[tokio::test]
+async fn test_disk_cache_metadata() {
+    let directory = directory("metadata");
+    let config = X509DiskCacheConfiguration {
+        directory: directory.clone(),
+        ..Default::default()
+    };
+
+    let redirects = vec![url("b c"), url("ca.cer")];
+    let transport = MockX509Transport::new().with_payload(
+        url("a"),
+        X509Payload {
+            redirects: redirects.clone(),
+            status: Some(200),
+            headers: HeaderMap::from_iter([(
+                HeaderName::from_static("x-cache"),
+                HeaderValue::from_static("a b%c"),
+            )]),
+            ..payload(3, X509Freshness::Unspecified)
+        },
+    );
+    let online = client(&transport, config.clone(), false);
+    assert!(online.get(&url("a")).await.is_ok());
+
+    let offline = client(
+        &MockX509Transport::new(),
+        X509DiskCacheConfiguration {
+            offline: true,
+            ..config
+        },
+        false,
+    );
+    let (_, chain) = offline.get_all_with_redirects(&url("a")).await.unwrap();
+    assert_eq!(vec![url("a"), url("b c"), url("ca.cer")], chain);
+
+    let response = offline
+        .get_response(&url("a"), &Default::default())
+        .await
+        .unwrap();
+    assert_eq!(url("ca.cer"), response.url);
+    assert_eq!(Some(200), response.status);
+    assert_eq!("a b%c", response.headers["x-cache"]);
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[tokio::test]
+async fn test_disk_cache_unparseable() {
+    let directory = directory("unparseable");
+    let config = X509DiskCacheConfiguration {
+        directory: directory.clone(),
+        ..Default::default()
+    };
+
+    // undetermined type, fails in strict mode
+    let transport = MockX509Transport::new()
+        .with_payload(url("a"), X509Payload::new(X509Hint::None, vec![1, 2, 3]));
+    let online = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            strict: true,
+            disk_cache: Some(config.clone()),
+            ..Default::default()
+        },
+        transport,
+    );
+    assert!(online.get(&url("a")).await.is_err());
+
+    let offline = client(
+        &MockX509Transport::new(),
+        X509DiskCacheConfiguration {
+            offline: true,
+            ..config
+        },
+        false,
+    );
+    assert!(matches!(
+        offline.get(&url("a")).await,
+        Err(X509ClientError::Offline(_))
+    ));
+
+    let _ = fs::remove_dir_all(directory);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_disk_cache_directory() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let directory = directory("directory");
+    let config = X509DiskCacheConfiguration {
+        directory: directory.clone(),
+        ..Default::default()
+    };
+
+    let transport = MockX509Transport::new()
+        .with_payload(url("a"), payload(3, X509Freshness::Unspecified))
+        .with_payload(url("b"), payload(3, X509Freshness::Unspecified));
+    let online = client(&transport, config.clone(), false);
+    assert!(online.get(&url("a")).await.is_ok());
+    assert_eq!(
+        0o700,
+        fs::metadata(&directory).unwrap().permissions().mode() & 0o777
+    );
+    // no temporary file left behind
+    assert_eq!(1, fs::read_dir(&directory).unwrap().count());
+
+    // a directory owned by another user is neither read nor written, requires privileges to set up
+    if std::os::unix::fs::chown(&directory, Some(65534), None).is_ok() {
+        assert!(online.get(&url("b")).await.is_ok());
+        assert_eq!(1, fs::read_dir(&directory).unwrap().count());
+
+        let offline = client(
+            &MockX509Transport::new(),
+            X509DiskCacheConfiguration {
+                offline: true,
+                ..config
+            },
+            false,
+        );
+        assert!(matches!(
+            offline.get(&url("a")).await,
+            Err(X509ClientError::Offline(_))
+        ));
+    }
+
+    fs::remove_dir_all(directory).unwrap();
+}
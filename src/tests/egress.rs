@@ -0,0 +1,170 @@
+use crate::api::{X509Hint, X509Payload};
+use crate::provided::debug::DebugX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::tests::http::{HttpResponse, HttpServer};
+use crate::{X509Client, X509ClientConfiguration, X509ClientError, X509EgressPolicy};
+use url::Url;
+
+fn check(policy: &X509EgressPolicy, url: &str) -> bool {
+    policy.check_url(&Url::parse(url).unwrap()).is_ok()
+}
+
+#[test]
+fn test_egress_private() {
+    let policy = X509EgressPolicy::default();
+
+    for url in [
+        "http://127.0.0.1/ca.cer",
+        "http://10.0.0.1/ca.cer",
+        "http://172.16.0.1/ca.cer",
+        "http://192.168.1.1/ca.cer",
+        "http://169.254.169.254/latest/meta-data",
+        "http://100.64.0.1/ca.cer",
+        "http://0.0.0.0/ca.cer",
+        "http://[::1]/ca.cer",
+        "http://[fd00::1]/ca.cer",
+        "http://[fe80::1]/ca.cer",
+        "http://[::ffff:127.0.0.1]/ca.cer",
+        "http://192.0.0.170/ca.cer",
+        "http://198.18.0.1/ca.cer",
+        "http://198.19.255.255/ca.cer",
+        "http://224.0.0.1/ca.cer",
+        "http://239.255.255.250/ca.cer",
+        "http://[ff02::1]/ca.cer",
+        "http://[64:ff9b::a9fe:a9fe]/ca.cer",
+        "http://[64:ff9b::7f00:1]/ca.cer",
+        "http://[2002:7f00:1::]/ca.cer",
+        "http://[2002:a9fe:a9fe::]/ca.cer",
+        "http://[2001:0:4136:e378:8000:63bf:80ff:fffe]/ca.cer",
+        "http://[2001:0:4136:e378:8000:63bf:5601:5601]/ca.cer",
+    ] {
+        assert!(!check(&policy, url), "{}", url);
+    }
+
+    for url in [
+        "http://8.8.8.8/ca.cer",
+        "http://100.128.0.1/ca.cer",
+        "http://192.0.1.1/ca.cer",
+        "http://198.20.0.1/ca.cer",
+        "http://[64:ff9b::808:808]/ca.cer",
+        "http://[2002:808:808::]/ca.cer",
+        "http://[2001:0:4136:e378:8000:63bf:f7f7:f7f7]/ca.cer",
+        "http://[2001:4860:4860::8888]/ca.cer",
+        "http://crt.example.com/ca.cer",
+    ] {
+        assert!(check(&policy, url), "{}", url);
+    }
+
+    let policy = X509EgressPolicy {
+        block_private: false,
+        ..Default::default()
+    };
+    assert!(check(&policy, "http://10.0.0.1/ca.cer"));
+}
+
+#[test]
+fn test_egress_hosts_ports() {
+    let policy = X509EgressPolicy {
+        allow_hosts: vec!["example.com".to_string(), "8.8.8.8".to_string()],
+        deny_hosts: vec!["internal.example.com".to_string()],
+        allow_ports: vec![80, 443],
+        ..Default::default()
+    };
+
+    assert!(check(&policy, "http://example.com/ca.cer"));
+    assert!(check(&policy, "https://crt.EXAMPLE.com/ca.cer"));
+    assert!(check(&policy, "http://8.8.8.8/ca.cer"));
+    assert!(!check(&policy, "http://notexample.com/ca.cer"));
+    assert!(!check(&policy, "http://internal.example.com/ca.cer"));
+    assert!(!check(&policy, "http://a.internal.example.com/ca.cer"));
+    assert!(!check(&policy, "http://example.com:8080/ca.cer"));
+    assert!(!check(&policy, "ldap://example.com/cn=ca"));
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_egress_default_ports() {
+    let policy = X509EgressPolicy {
+        allow_ports: vec![443, 25, 5432],
+        ..Default::default()
+    };
+
+    assert!(check(&policy, "tls://example.com"));
+    assert!(check(&policy, "smtp+starttls://example.com"));
+    assert!(check(&policy, "postgresql+starttls://example.com"));
+    assert!(!check(&policy, "imap+starttls://example.com"));
+    assert!(!check(&policy, "tls://example.com:8443"));
+}
+
+#[tokio::test]
+async fn test_egress_client() {
+    let url = Url::parse("http://169.254.169.254/ca.cer").unwrap();
+    let transport = MockX509Transport::new()
+        .with_payload(url.clone(), X509Payload::new(X509Hint::None, vec![1]));
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            egress: Some(X509EgressPolicy::default()),
+            ..Default::default()
+        },
+        transport.clone(),
+    );
+
+    assert!(matches!(
+        client.get(&url).await,
+        Err(X509ClientError::EgressDenied(_))
+    ));
+    assert!(transport.requests().is_empty());
+
+    // hosts resolving to private addresses
+    let url = Url::parse("http://localhost/ca.cer").unwrap();
+    assert!(matches!(
+        client.get(&url).await,
+        Err(X509ClientError::EgressDenied(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_egress_redirect() {
+    let server = HttpServer::start(|request| match request.path.as_str() {
+        "/redirect" => HttpResponse::new(302, b"").with_header(
+            "Location",
+            &format!(
+                "http://localhost:{}/ca.cer",
+                request.headers["host"].split(':').nth(1).unwrap()
+            ),
+        ),
+        _ => HttpResponse::new(200, &[1, 2, 3]),
+    })
+    .await;
+
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
+        egress: Some(X509EgressPolicy {
+            deny_hosts: vec!["localhost".to_string()],
+            block_private: false,
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+
+    assert!(client.get(&server.url("/ca.cer")).await.is_ok());
+    assert!(matches!(
+        client.get(&server.url("/redirect")).await,
+        Err(X509ClientError::EgressDenied(_))
+    ));
+    assert_eq!(2, server.requests().len());
+}
+
+#[tokio::test]
+async fn test_egress_resolve() {
+    let policy = X509EgressPolicy::default();
+    assert!(matches!(
+        policy.resolve("localhost").await,
+        Err(X509ClientError::EgressDenied(_))
+    ));
+
+    let policy = X509EgressPolicy {
+        block_private: false,
+        ..Default::default()
+    };
+    assert!(!policy.resolve("localhost").await.unwrap().is_empty());
+}
@@ -0,0 +1,170 @@
+use crate::provided::default::DefaultX509Iterator;
+use crate::{
+    X509Client, X509ClientConfiguration, X509ClientError, X509SchemePolicy, X509Type,
+    X509TypeConfiguration,
+};
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+#[tokio::test]
+async fn test_file_directory() {
+    let dir = directory("directory");
+    let url = Url::from_directory_path(&dir).unwrap();
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::Any,
+        ..Default::default()
+    });
+
+    let (certificates, errors) = client.get_all_with_errors(&url).await.unwrap();
+    assert_eq!(3, certificates.into_iter().len());
+
+    let mut errors = errors.into_iter().map(|e| e.path).collect::<Vec<_>>();
+    errors.sort();
+    let dir = fs::canonicalize(&dir).unwrap();
+    assert_eq!(
+        vec![dir.join("broken.cer"), dir.join("dangling.pem")],
+        errors
+    );
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::Any,
+        recursive: true,
+        ..Default::default()
+    });
+
+    assert_eq!(5, client.get_all(&url).await.unwrap().into_iter().len());
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::Any,
+        limit: Some(1024),
+        ..Default::default()
+    });
+
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::LimitExceeded { limit: 1024, .. })
+    ));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_file_types() {
+    let dir = directory("types");
+    fs::copy(dir.join("a.cer"), dir.join("d.crt")).unwrap();
+    fs::copy(dir.join("a.cer"), dir.join("e.der")).unwrap();
+    let url = Url::from_directory_path(&dir).unwrap();
+
+    let mut types = X509TypeConfiguration::default();
+    types.extensions.insert("crt".to_string(), X509Type::Cer);
+    types.extensions.insert("der".to_string(), X509Type::Cer);
+    types.extensions.remove("pem");
+    // hashed entries do not depend on the configured mappings
+    types
+        .media_types
+        .remove("application/pem-certificate-chain");
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::Any,
+        types,
+        ..Default::default()
+    });
+
+    // a.cer, d.crt and e.der, along with the fullchain of the hashed entry
+    let (certificates, errors) = client.get_all_with_errors(&url).await.unwrap();
+    assert_eq!(5, certificates.into_iter().len());
+
+    let mut errors = errors.into_iter().map(|e| e.path).collect::<Vec<_>>();
+    errors.sort();
+    let dir = fs::canonicalize(&dir).unwrap();
+    assert_eq!(
+        vec![dir.join("broken.cer"), dir.join("dangling.pem")],
+        errors
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_file_limit() {
+    let certificate = Path::new(file!())
+        .parent()
+        .unwrap()
+        .join("../provided/default/tests/resource.resources.ciph.xxx.cer");
+    let certificate = Url::from_file_path(fs::canonicalize(certificate).unwrap()).unwrap();
+    let zero = Url::from_file_path("/dev/zero").unwrap();
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::Any,
+        limit: Some(525),
+        ..Default::default()
+    });
+
+    assert!(client.get_all(&certificate).await.is_ok());
+    assert!(matches!(
+        client.get_all(&zero).await,
+        Err(X509ClientError::LimitExceeded {
+            size: 526,
+            limit: 525
+        })
+    ));
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::Any,
+        limit: Some(524),
+        ..Default::default()
+    });
+
+    assert!(matches!(
+        client.get_all(&certificate).await,
+        Err(X509ClientError::LimitExceeded {
+            size: 525,
+            limit: 524
+        })
+    ));
+}
+
+/// Temporary directory of certificate files, hashed entries and files which are not recognised or fail to load
+fn directory(name: &str) -> PathBuf {
+    let resources = Path::new(file!())
+        .parent()
+        .unwrap()
+        .join("../provided/default/tests");
+    let resources = fs::canonicalize(resources).unwrap();
+
+    let dir = std::env::temp_dir().join(format!("x509-client-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("sub")).unwrap();
+
+    fs::copy(
+        resources.join("resource.resources.ciph.xxx.cer"),
+        dir.join("a.cer"),
+    )
+    .unwrap();
+    fs::copy(
+        resources.join("resource.resources.ciph.xxx-fullchain.pem"),
+        dir.join("b.pem"),
+    )
+    .unwrap();
+    fs::copy(
+        resources.join("resource.resources.ciph.xxx.p7c"),
+        dir.join("sub/c.p7c"),
+    )
+    .unwrap();
+    symlink(dir.join("b.pem"), dir.join("1a2b3c4d.0")).unwrap();
+    symlink(dir.join("missing.pem"), dir.join("dangling.pem")).unwrap();
+    fs::write(dir.join("broken.cer"), b"broken").unwrap();
+    fs::write(dir.join("1a2b3c4d.r0"), b"crl").unwrap();
+    fs::write(dir.join("readme.txt"), b"readme").unwrap();
+
+    dir
+}
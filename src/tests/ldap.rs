@@ -0,0 +1,184 @@
+use crate::provided::default::DefaultX509Iterator;
+use crate::{
+    X509Client, X509ClientConfiguration, X509ClientError, X509EgressPolicy, X509SchemePolicy,
+};
+use std::path::Path;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use url::Url;
+
+#[tokio::test]
+async fn test_ldap() {
+    let certificate = std::fs::read(
+        Path::new(file!())
+            .parent()
+            .unwrap()
+            .join("../provided/default/tests/resource.resources.ciph.xxx.cer"),
+    )
+    .unwrap();
+
+    let pair = tlv(
+        0x30,
+        &[&tlv(0xa0, &[&certificate]), &tlv(0xa1, &[&certificate])],
+    );
+
+    let attributes = vec![
+        ("cACertificate;binary", vec![certificate.clone()]),
+        ("crossCertificatePair;binary", vec![pair]),
+        ("objectClass", vec![b"top".to_vec()]),
+    ];
+
+    let port = ldap_server(attributes).await;
+    let url = Url::parse(&format!("ldap://127.0.0.1:{}/cn=CA,dc=ciph,dc=xxx", port)).unwrap();
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::default(),
+        limit: None,
+        ..Default::default()
+    });
+    assert_eq!(3, client.get_all(&url).await.unwrap().into_iter().len());
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::default(),
+        limit: Some(certificate.len()),
+        ..Default::default()
+    });
+    // checked as the certificates of the entry are read
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::LimitExceeded { size, .. }) if size == 2 * certificate.len()
+    ));
+
+    // connects to the checked addresses
+    let url = Url::parse(&format!("ldap://localhost:{}/cn=CA,dc=ciph,dc=xxx", port)).unwrap();
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::default(),
+        egress: Some(X509EgressPolicy {
+            block_private: false,
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+    assert_eq!(3, client.get_all(&url).await.unwrap().into_iter().len());
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::default(),
+        egress: Some(X509EgressPolicy::default()),
+        ..Default::default()
+    });
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::EgressDenied(_))
+    ));
+
+    // the server certificate could not be verified against the host name
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::default(),
+        egress: Some(X509EgressPolicy {
+            block_private: false,
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+    let url = Url::parse(&format!("ldaps://localhost:{}/cn=CA,dc=ciph,dc=xxx", port)).unwrap();
+    assert!(matches!(
+        client.get_all(&url).await,
+        Err(X509ClientError::EgressDenied(_))
+    ));
+}
+
+/// Minimal LDAP stand-in, answers every search request with a single entry
+async fn ldap_server(attributes: Vec<(&'static str, Vec<Vec<u8>>)>) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let attributes = attributes
+        .into_iter()
+        .map(|(attr, values)| {
+            let values = values
+                .iter()
+                .map(|v| tlv(0x04, &[v]))
+                .collect::<Vec<_>>()
+                .concat();
+            tlv(
+                0x30,
+                &[&tlv(0x04, &[attr.as_bytes()]), &tlv(0x31, &[&values])],
+            )
+        })
+        .collect::<Vec<_>>()
+        .concat();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let attributes = attributes.clone();
+            tokio::spawn(async move {
+                while let Some((id, op)) = read_message(&mut stream).await {
+                    // SearchRequest
+                    if op != 0x63 {
+                        continue;
+                    }
+                    let entry = tlv(
+                        0x64,
+                        &[
+                            &tlv(0x04, &[b"cn=CA,dc=ciph,dc=xxx"]),
+                            &tlv(0x30, &[&attributes]),
+                        ],
+                    );
+                    let done = tlv(
+                        0x65,
+                        &[&tlv(0x0a, &[&[0]]), &tlv(0x04, &[]), &tlv(0x04, &[])],
+                    );
+                    for op in [entry, done] {
+                        let message = tlv(0x30, &[&tlv(0x02, &[&id]), &op]);
+                        stream.write_all(&message).await.unwrap();
+                    }
+                }
+            });
+        }
+    });
+
+    port
+}
+
+/// Read an LDAPMessage, returning the message id and the protocol operation tag
+async fn read_message(stream: &mut TcpStream) -> Option<(Vec<u8>, u8)> {
+    let mut header = [0u8; 2];
+    stream.read_exact(&mut header).await.ok()?;
+    let len = match header[1] {
+        len if len < 0x80 => len as usize,
+        len => {
+            let mut octets = vec![0u8; (len & 0x7f) as usize];
+            stream.read_exact(&mut octets).await.ok()?;
+            octets
+                .iter()
+                .fold(0usize, |acc, b| (acc << 8) | *b as usize)
+        }
+    };
+    let mut content = vec![0u8; len];
+    stream.read_exact(&mut content).await.ok()?;
+
+    let id_len = content[1] as usize;
+    Some((content[2..2 + id_len].to_vec(), content[2 + id_len]))
+}
+
+fn tlv(tag: u8, content: &[&[u8]]) -> Vec<u8> {
+    let content = content.concat();
+    let mut r = vec![tag];
+    match content.len() {
+        len if len < 0x80 => r.push(len as u8),
+        len => {
+            let octets = len.to_be_bytes();
+            let octets = &octets[octets.iter().position(|b| *b != 0).unwrap()..];
+            r.push(0x80 | octets.len() as u8);
+            r.extend_from_slice(octets);
+        }
+    }
+    r.extend(content);
+    r
+}
@@ -1,4 +1,33 @@
+mod any;
+#[cfg(all(feature = "archive", feature = "default"))]
+mod archive;
+mod batch;
+#[cfg(feature = "blocking")]
+mod blocking;
+mod cache;
 mod client;
+#[cfg(all(feature = "compression", feature = "default"))]
+mod compression;
+mod data;
+mod disk_cache;
+mod egress;
+#[cfg(all(unix, feature = "default"))]
+mod file;
+mod http;
 mod iter;
+#[cfg(all(feature = "ldap", feature = "default"))]
+mod ldap;
 mod parse;
+mod redirect;
+mod response;
+mod retry;
+mod revalidate;
+#[cfg(feature = "default")]
+mod sniff;
+#[cfg(all(feature = "tls", feature = "default"))]
+mod starttls;
+mod stream;
+mod timeout;
+#[cfg(all(feature = "tls", feature = "default"))]
+mod tls;
 mod x509type;
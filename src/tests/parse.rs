@@ -50,3 +50,45 @@ fn test_x509parse_relaxed() {
 
     assert!(parser.parse(&X509Type::Unknown, vec![FAIL_ON_ANY]).is_err());
 }
+
+#[test]
+fn test_x509parse_detect() {
+    let parser: X509Parse<TestX509Iterator> = X509Parse::new(true);
+    assert_eq!(
+        X509Type::Pem,
+        parser.parse(&X509Type::Pem, vec![FAIL_NEVER]).unwrap().1
+    );
+
+    let parser: X509Parse<TestX509Iterator> = X509Parse::new(false);
+    assert_eq!(
+        X509Type::Pkcs7,
+        parser.parse(&X509Type::Pkcs7, vec![FAIL_ON_CER]).unwrap().1
+    );
+    assert_eq!(
+        X509Type::Cer,
+        parser.parse(&X509Type::Pem, vec![FAIL_ON_PEM]).unwrap().1
+    );
+    assert_eq!(
+        X509Type::Pkcs7,
+        parser
+            .parse(&X509Type::Unknown, vec![FAIL_ON_CER])
+            .unwrap()
+            .1
+    );
+    assert_eq!(
+        X509Type::Pem,
+        parser
+            .parse(&X509Type::Unknown, vec![FAIL_ON_CER, FAIL_ON_PKCS7])
+            .unwrap()
+            .1
+    );
+    // a CRL hint does not mask certificates
+    assert_eq!(
+        X509Type::Cer,
+        parser.parse(&X509Type::Crl, vec![FAIL_NEVER]).unwrap().1
+    );
+    assert_eq!(
+        X509Type::Crl,
+        parser.parse(&X509Type::Crl, vec![FAIL_ON_ANY]).unwrap().1
+    );
+}
@@ -0,0 +1,105 @@
+use crate::provided::debug::DebugX509Iterator;
+use crate::provided::transport::ReqwestX509Transport;
+use crate::tests::http::{HttpResponse, HttpServer};
+use crate::{X509Client, X509ClientConfiguration, X509ClientError, X509RedirectConfiguration};
+use url::Url;
+
+/// Redirects `/<n>` to `/<n - 1>`, `/0` to the supplied location, serves everything else
+async fn server(location: &'static str) -> HttpServer {
+    HttpServer::start(move |request| {
+        let path = request.path.trim_start_matches('/');
+        match path.parse::<usize>() {
+            Ok(0) => HttpResponse::new(302, b"").with_header("Location", location),
+            Ok(n) => HttpResponse::new(301, b"").with_header("Location", &format!("/{}", n - 1)),
+            Err(_) => HttpResponse::new(200, &[1, 2, 3]),
+        }
+    })
+    .await
+}
+
+fn client(redirect: X509RedirectConfiguration) -> X509Client<DebugX509Iterator> {
+    X509Client::new(X509ClientConfiguration {
+        redirect,
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn test_redirect_chain() {
+    let server = server("ca.cer").await;
+    let client = client(X509RedirectConfiguration::default());
+
+    let (certificates, chain) = client
+        .get_all_with_redirects(&server.url("/2"))
+        .await
+        .unwrap();
+    assert_eq!(1, certificates.into_iter().count());
+    assert_eq!(
+        vec![
+            server.url("/2"),
+            server.url("/1"),
+            server.url("/0"),
+            server.url("/ca.cer")
+        ],
+        chain
+    );
+
+    let (_, chain) = client
+        .get_all_with_redirects(&server.url("/ca.cer"))
+        .await
+        .unwrap();
+    assert_eq!(vec![server.url("/ca.cer")], chain);
+}
+
+#[tokio::test]
+async fn test_redirect_max() {
+    let server = server("ca.cer").await;
+    let client = client(X509RedirectConfiguration {
+        max: 2,
+        ..Default::default()
+    });
+
+    assert!(client.get_all(&server.url("/1")).await.is_ok());
+    assert!(matches!(
+        client.get_all(&server.url("/2")).await,
+        Err(X509ClientError::RedirectRefused(_))
+    ));
+    assert_eq!(6, server.requests().len());
+}
+
+#[tokio::test]
+async fn test_redirect_cross_host() {
+    let server = server("http://localhost:1/ca.cer").await;
+    let client = client(X509RedirectConfiguration {
+        allow_cross_host: false,
+        ..Default::default()
+    });
+
+    assert!(matches!(
+        client.get_all(&server.url("/1")).await,
+        Err(X509ClientError::RedirectRefused(e)) if e.starts_with("cross-host")
+    ));
+}
+
+#[test]
+fn test_redirect_downgrade() {
+    let https = Url::parse("https://localhost/ca.cer").unwrap();
+    let http = Url::parse("http://localhost/ca.cer").unwrap();
+
+    let transport = ReqwestX509Transport::with_configuration(&X509ClientConfiguration::default());
+    assert!(transport.check_redirect(&https, &http, 0).is_ok());
+
+    let transport = ReqwestX509Transport::with_configuration(&X509ClientConfiguration {
+        redirect: X509RedirectConfiguration {
+            allow_downgrade: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    assert!(matches!(
+        transport.check_redirect(&https, &http, 0),
+        Err(X509ClientError::RedirectRefused(_))
+    ));
+    assert!(transport.check_redirect(&http, &https, 0).is_ok());
+    assert!(transport.check_redirect(&http, &http, 0).is_ok());
+}
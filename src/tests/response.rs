@@ -0,0 +1,54 @@
+use crate::api::X509DocumentFormat;
+use crate::provided::debug::DebugX509Iterator;
+use crate::tests::http::{HttpResponse, HttpServer};
+use crate::{X509Client, X509ClientConfiguration, X509RequestOptions, X509Type};
+use http::header::CONTENT_TYPE;
+
+#[tokio::test]
+async fn test_response() {
+    let server = HttpServer::start(|request| match request.path.as_str() {
+        "/redirect" => HttpResponse::new(302, b"").with_header("Location", "/ca.cer"),
+        "/ca.cer" => {
+            HttpResponse::new(200, &[1, 2, 3]).with_header("Content-Type", "application/pkix-cert")
+        }
+        _ => HttpResponse::new(200, &[1, 2, 3, 4]),
+    })
+    .await;
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration::default());
+
+    let response = client
+        .get_response(&server.url("/redirect"), &X509RequestOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(1, response.certificates.into_iter().count());
+    assert_eq!(server.url("/ca.cer"), response.url);
+    assert_eq!(vec![server.url("/ca.cer")], response.redirects);
+    assert_eq!(Some(200), response.status);
+    assert_eq!("application/pkix-cert", response.headers[CONTENT_TYPE]);
+    assert_eq!(3, response.size);
+    assert_eq!(
+        vec![X509DocumentFormat {
+            path: None,
+            hint: X509Type::Cer,
+            format: X509Type::Cer,
+            count: 1,
+            sniffed: None,
+        }],
+        response.formats
+    );
+    assert!(!response.formats[0].is_fallback());
+    assert!(response.errors.is_empty());
+    assert!(!response.elapsed.is_zero());
+
+    // relaxed fallback without content type
+    let response = client
+        .get_response(&server.url("/unknown"), &X509RequestOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(server.url("/unknown"), response.url);
+    assert!(response.redirects.is_empty());
+    assert_eq!(4, response.size);
+    assert_eq!(X509Type::Unknown, response.formats[0].hint);
+    assert_eq!(X509Type::Cer, response.formats[0].format);
+    assert!(response.formats[0].is_fallback());
+}
@@ -0,0 +1,126 @@
+use crate::provided::debug::DebugX509Iterator;
+use crate::tests::http::{HttpResponse, HttpServer};
+use crate::{X509Client, X509ClientConfiguration, X509ClientError, X509RetryConfiguration};
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Serves `failures` responses with `status`, then succeeds
+async fn failing_server(
+    failures: usize,
+    status: u16,
+    retry_after: Option<&'static str>,
+) -> HttpServer {
+    let count = Arc::new(AtomicUsize::new(0));
+    HttpServer::start(move |_| {
+        if count.fetch_add(1, Ordering::SeqCst) < failures {
+            let response = HttpResponse::new(status, b"");
+            return match retry_after {
+                Some(retry_after) => response.with_header("Retry-After", retry_after),
+                None => response,
+            };
+        }
+        HttpResponse::new(200, &[1, 2, 3])
+    })
+    .await
+}
+
+fn client(attempts: usize) -> X509Client<DebugX509Iterator> {
+    X509Client::new(X509ClientConfiguration {
+        retry: Some(X509RetryConfiguration {
+            attempts,
+            backoff: Duration::from_millis(1),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn test_retry_recovers() {
+    let server = failing_server(2, 503, None).await;
+    assert!(client(3).get(&server.url("/ca.cer")).await.is_ok());
+    assert_eq!(3, server.requests().len());
+
+    let server = failing_server(1, 429, Some("0")).await;
+    assert!(client(3).get(&server.url("/ca.cer")).await.is_ok());
+    assert_eq!(2, server.requests().len());
+}
+
+#[tokio::test]
+async fn test_retry_exhausted() {
+    let server = failing_server(5, 500, None).await;
+    assert!(matches!(
+        client(3).get(&server.url("/ca.cer")).await,
+        Err(X509ClientError::HttpStatus { status: 500, .. })
+    ));
+    assert_eq!(3, server.requests().len());
+
+    // no retry policy, a single attempt
+    let server = failing_server(1, 503, None).await;
+    let client = X509Client::<DebugX509Iterator>::default();
+    assert!(client.get(&server.url("/ca.cer")).await.is_err());
+    assert_eq!(1, server.requests().len());
+}
+
+#[tokio::test]
+async fn test_retry_permanent() {
+    let server = failing_server(1, 404, None).await;
+    assert!(matches!(
+        client(3).get(&server.url("/ca.cer")).await,
+        Err(X509ClientError::HttpStatus { status: 404, .. })
+    ));
+    assert_eq!(1, server.requests().len());
+
+    // the server asks to wait longer than the max backoff
+    let server = failing_server(1, 503, Some("3600")).await;
+    match client(3).get(&server.url("/ca.cer")).await {
+        Err(e) => assert_eq!(Some(Duration::from_secs(3600)), e.retry_after()),
+        Ok(_) => panic!("expected error"),
+    }
+    assert_eq!(1, server.requests().len());
+}
+
+#[test]
+fn test_retry_classification() {
+    let retryable = |e: X509ClientError| e.is_retryable();
+
+    assert!(retryable(
+        io::Error::from(io::ErrorKind::ConnectionReset).into()
+    ));
+    assert!(retryable(io::Error::from(io::ErrorKind::TimedOut).into()));
+    assert!(!retryable(io::Error::from(io::ErrorKind::NotFound).into()));
+    assert!(retryable(X509ClientError::HttpStatus {
+        status: 502,
+        retry_after: None
+    }));
+    assert!(!retryable(X509ClientError::HttpStatus {
+        status: 403,
+        retry_after: None
+    }));
+    assert!(!retryable(X509ClientError::LimitExceeded {
+        size: 2,
+        limit: 1
+    }));
+    assert!(!retryable(X509ClientError::Error("parse".to_string())));
+
+    #[cfg(feature = "ldap")]
+    {
+        let ldap_result = |rc| ldap3::LdapResult {
+            rc,
+            matched: String::new(),
+            text: String::new(),
+            refs: vec![],
+            ctrls: vec![],
+        };
+        assert!(retryable(
+            ldap3::LdapError::from(io::Error::from(io::ErrorKind::ConnectionRefused)).into()
+        ));
+        assert!(retryable(ldap3::LdapError::EndOfStream.into()));
+        assert!(retryable(ldap3::LdapError::from(ldap_result(52)).into()));
+        // no such object
+        assert!(!retryable(ldap3::LdapError::from(ldap_result(32)).into()));
+        assert!(!retryable(ldap3::LdapError::FilterParsing.into()));
+    }
+}
@@ -0,0 +1,172 @@
+use crate::api::{X509Hint, X509Payload, X509Validators};
+use crate::provided::debug::DebugX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::tests::http::{HttpResponse, HttpServer};
+use crate::{X509CacheConfiguration, X509Client, X509ClientConfiguration};
+use std::time::Duration;
+use url::Url;
+
+const ETAG: &str = "\"v1\"";
+const LAST_MODIFIED: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+
+#[tokio::test]
+async fn test_revalidate_http() {
+    let server = HttpServer::start(|request| {
+        if request.headers.get("if-none-match").map(String::as_str) == Some(ETAG) {
+            return HttpResponse::new(304, b"");
+        }
+        HttpResponse::new(200, &[1, 2, 3])
+            .with_header("Content-Type", "application/pkix-cert")
+            .with_header("ETag", ETAG)
+            .with_header("Last-Modified", LAST_MODIFIED)
+    })
+    .await;
+    let url = server.url("/ca.cer");
+
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        revalidate: true,
+        ..Default::default()
+    });
+
+    assert_eq!(&[1u8, 2, 3][..], client.get(&url).await.unwrap());
+    assert_eq!(&[1u8, 2, 3][..], client.get(&url).await.unwrap());
+    client.clear_validators();
+    assert_eq!(&[1u8, 2, 3][..], client.get(&url).await.unwrap());
+
+    let requests = server.requests();
+    assert_eq!(3, requests.len());
+    assert!(requests.iter().all(|r| r.path == "/ca.cer"));
+    assert_eq!(None, requests[0].headers.get("if-none-match"));
+    assert_eq!(
+        Some(ETAG),
+        requests[1].headers.get("if-none-match").map(String::as_str)
+    );
+    assert_eq!(
+        Some(LAST_MODIFIED),
+        requests[1]
+            .headers
+            .get("if-modified-since")
+            .map(String::as_str)
+    );
+    assert_eq!(None, requests[2].headers.get("if-none-match"));
+
+    // the revalidated response reports the not modified status along with the previous certificates
+    let response = client
+        .get_response(&url, &Default::default())
+        .await
+        .unwrap();
+    assert_eq!(Some(304), response.status);
+    assert_eq!(3, response.size);
+    assert!(response.headers.get("content-type").is_none());
+    assert_eq!(
+        vec![&[1u8, 2, 3][..]],
+        response.certificates.into_iter().collect::<Vec<_>>()
+    );
+
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        ..Default::default()
+    });
+
+    assert_eq!(&[1u8, 2, 3][..], client.get(&url).await.unwrap());
+    assert_eq!(&[1u8, 2, 3][..], client.get(&url).await.unwrap());
+    assert!(server.requests()[4..]
+        .iter()
+        .all(|r| !r.headers.contains_key("if-none-match")));
+}
+
+#[tokio::test]
+async fn test_revalidate_capacity() {
+    let server = HttpServer::start(|request| {
+        if request.headers.get("if-none-match").map(String::as_str) == Some(ETAG) {
+            return HttpResponse::new(304, b"");
+        }
+        HttpResponse::new(200, &[1, 2, 3])
+            .with_header("Content-Type", "application/pkix-cert")
+            .with_header("ETag", ETAG)
+    })
+    .await;
+
+    // validators of a single url are remembered, payloads are not cached
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        revalidate: true,
+        cache: Some(X509CacheConfiguration {
+            ttl: Duration::ZERO,
+            capacity: 1,
+        }),
+        ..Default::default()
+    });
+
+    for path in ["/a.cer", "/b.cer", "/a.cer", "/a.cer"] {
+        assert_eq!(
+            &[1u8, 2, 3][..],
+            client.get(&server.url(path)).await.unwrap()
+        );
+    }
+
+    let requests = server.requests();
+    assert_eq!(
+        vec![false, false, false, true],
+        requests
+            .iter()
+            .map(|r| r.headers.contains_key("if-none-match"))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_revalidate_no_store() {
+    let server = HttpServer::start(|request| {
+        if request.headers.get("if-none-match").map(String::as_str) == Some(ETAG) {
+            return HttpResponse::new(304, b"");
+        }
+        HttpResponse::new(200, &[1, 2, 3])
+            .with_header("Content-Type", "application/pkix-cert")
+            .with_header("Cache-Control", "no-store")
+            .with_header("ETag", ETAG)
+    })
+    .await;
+    let url = server.url("/ca.cer");
+
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        revalidate: true,
+        ..Default::default()
+    });
+
+    assert_eq!(&[1u8, 2, 3][..], client.get(&url).await.unwrap());
+    assert_eq!(&[1u8, 2, 3][..], client.get(&url).await.unwrap());
+    assert!(server
+        .requests()
+        .iter()
+        .all(|r| !r.headers.contains_key("if-none-match")));
+}
+
+#[tokio::test]
+async fn test_revalidate_mock() {
+    let url = Url::parse("http://localhost/ca.cer").unwrap();
+    let transport = MockX509Transport::new().with_payload(
+        url.clone(),
+        X509Payload {
+            validators: X509Validators {
+                etag: Some(ETAG.to_string()),
+                last_modified: None,
+            },
+            ..X509Payload::new(X509Hint::None, vec![1, 2, 3])
+        },
+    );
+
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            revalidate: true,
+            ..Default::default()
+        },
+        transport.clone(),
+    );
+
+    assert_eq!(&[1u8, 2, 3][..], client.get(&url).await.unwrap());
+    assert_eq!(&[1u8, 2, 3][..], client.get(&url).await.unwrap());
+    assert_eq!(2, transport.requests().len());
+}
@@ -0,0 +1,145 @@
+use crate::provided::default::DefaultX509Iterator;
+use crate::tests::tls::acceptor;
+use crate::{X509Client, X509ClientConfiguration, X509SchemePolicy};
+use openssl::ssl::Ssl;
+use std::pin::Pin;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_openssl::SslStream;
+use url::Url;
+
+#[tokio::test]
+async fn test_starttls() {
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::default(),
+        limit: None,
+        ..Default::default()
+    });
+
+    for scheme in [
+        "smtp+starttls",
+        "imap+starttls",
+        "pop3+starttls",
+        "ldap+starttls",
+        "postgres+starttls",
+    ] {
+        let port = starttls_server(scheme, true).await;
+        let url = Url::parse(&format!("{}://localhost:{}", scheme, port)).unwrap();
+        assert_eq!(
+            2,
+            client.get_all(&url).await.unwrap().into_iter().len(),
+            "{}",
+            scheme
+        );
+
+        let port = starttls_server(scheme, false).await;
+        let url = Url::parse(&format!("{}://localhost:{}", scheme, port)).unwrap();
+        assert!(client.get_all(&url).await.is_err(), "{}", scheme);
+    }
+}
+
+/// Local stand-in speaking the protocol greeting and upgrade. Refuses the upgrade if `accept` is false.
+async fn starttls_server(scheme: &'static str, accept: bool) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let acceptor = acceptor();
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        if !upgrade(scheme, accept, &mut stream).await || !accept {
+            return;
+        }
+        let ssl = Ssl::new(acceptor.context()).unwrap();
+        let mut stream = SslStream::new(ssl, stream).unwrap();
+        let _ = Pin::new(&mut stream).accept().await;
+    });
+
+    port
+}
+
+async fn upgrade(scheme: &str, accept: bool, stream: &mut TcpStream) -> bool {
+    match scheme {
+        "smtp+starttls" => {
+            stream
+                .write_all(b"220-smtp.ciph.xxx ESMTP\r\n220 ready\r\n")
+                .await
+                .unwrap();
+            expect_line(stream, "EHLO").await;
+            stream
+                .write_all(b"250-smtp.ciph.xxx\r\n250-STARTTLS\r\n250 SIZE 1024\r\n")
+                .await
+                .unwrap();
+            expect_line(stream, "STARTTLS").await;
+            let response: &[u8] = if accept {
+                b"220 go ahead\r\n"
+            } else {
+                b"454 unavailable\r\n"
+            };
+            stream.write_all(response).await.unwrap();
+        }
+        "imap+starttls" => {
+            stream.write_all(b"* OK IMAP4rev1 ready\r\n").await.unwrap();
+            expect_line(stream, "a001 STARTTLS").await;
+            let response: &[u8] = if accept {
+                b"* CAPABILITY IMAP4rev1\r\na001 OK begin tls\r\n"
+            } else {
+                b"a001 BAD not supported\r\n"
+            };
+            stream.write_all(response).await.unwrap();
+        }
+        "pop3+starttls" => {
+            stream.write_all(b"+OK POP3 ready\r\n").await.unwrap();
+            expect_line(stream, "STLS").await;
+            let response: &[u8] = if accept {
+                b"+OK begin tls\r\n"
+            } else {
+                b"-ERR not supported\r\n"
+            };
+            stream.write_all(response).await.unwrap();
+        }
+        "ldap+starttls" => {
+            let mut request = [0u8; 31];
+            stream.read_exact(&mut request).await.unwrap();
+            assert!(request.ends_with(b"1.3.6.1.4.1.1466.20037"));
+            let result_code = if accept { 0 } else { 2 };
+            stream
+                .write_all(&[
+                    0x30,
+                    0x0c,
+                    0x02,
+                    0x01,
+                    0x01,
+                    0x78,
+                    0x07,
+                    0x0a,
+                    0x01,
+                    result_code,
+                    0x04,
+                    0x00,
+                    0x04,
+                    0x00,
+                ])
+                .await
+                .unwrap();
+        }
+        "postgres+starttls" => {
+            let mut request = [0u8; 8];
+            stream.read_exact(&mut request).await.unwrap();
+            assert_eq!([0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f], request);
+            stream
+                .write_all(if accept { b"S" } else { b"N" })
+                .await
+                .unwrap();
+        }
+        _ => return false,
+    }
+    true
+}
+
+async fn expect_line(stream: &mut TcpStream, prefix: &str) {
+    let mut line = String::new();
+    let mut reader = BufReader::new(stream);
+    reader.read_line(&mut line).await.unwrap();
+    assert!(line.starts_with(prefix), "{}", line);
+}
@@ -0,0 +1,141 @@
+use crate::api::{X509Hint, X509Payload};
+use crate::provided::debug::DebugX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::tests::http::{HttpResponse, HttpServer};
+use crate::{X509Client, X509ClientConfiguration, X509ClientError, X509SchemePolicy};
+use futures::StreamExt;
+use url::Url;
+
+const BLOCK_1: &str = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n";
+const BLOCK_2: &str = "-----BEGIN CERTIFICATE-----\r\nMIIC\r\n-----END CERTIFICATE-----\r\n";
+
+fn client(
+    url: &Url,
+    hint: X509Hint,
+    body: &[u8],
+    limit: Option<usize>,
+) -> X509Client<DebugX509Iterator, MockX509Transport> {
+    let transport = MockX509Transport::new()
+        .with_payload(url.clone(), X509Payload::new(hint, body.to_vec()))
+        .with_chunk_size(7);
+    X509Client::with_transport(
+        X509ClientConfiguration {
+            limit,
+            ..Default::default()
+        },
+        transport,
+    )
+}
+
+#[tokio::test]
+async fn test_stream_pem() {
+    let url = Url::parse("http://localhost/ca.pem").unwrap();
+    let body = format!("{}{}\n", BLOCK_1, BLOCK_2);
+
+    for hint in [
+        X509Hint::ContentType("application/x-pem-file".to_string()),
+        X509Hint::None,
+    ] {
+        let client = client(&url, hint, body.as_bytes(), None);
+        let certificates = client
+            .get_stream(&url)
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            vec![BLOCK_1.as_bytes(), BLOCK_2.as_bytes()],
+            certificates.iter().map(|c| c.as_ref()).collect::<Vec<_>>()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_stream_limit() {
+    let url = Url::parse("http://localhost/ca.pem").unwrap();
+    let body = format!("{}{}", BLOCK_1, BLOCK_2);
+    let client = client(
+        &url,
+        X509Hint::None,
+        body.as_bytes(),
+        Some(BLOCK_1.len() + 7),
+    );
+
+    let mut stream = Box::pin(client.get_stream(&url));
+    assert_eq!(BLOCK_1.as_bytes(), stream.next().await.unwrap().unwrap());
+    assert!(matches!(
+        stream.next().await,
+        Some(Err(X509ClientError::LimitExceeded { .. }))
+    ));
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_stream_buffered() {
+    let url = Url::parse("http://localhost/ca.cer").unwrap();
+    let client = client(&url, X509Hint::None, &[1, 2, 3, 4, 5, 6, 7, 8, 9], None);
+
+    let certificates = client.get_stream(&url).collect::<Vec<_>>().await;
+    assert_eq!(1, certificates.len());
+    assert_eq!(
+        &[1, 2, 3, 4, 5, 6, 7, 8, 9],
+        certificates[0].as_ref().unwrap().as_ref()
+    );
+}
+
+#[tokio::test]
+async fn test_stream_misleading_hint() {
+    let url = Url::parse("http://localhost/ca.pem").unwrap();
+    let body = include_bytes!("resource.resources.ciph.xxx.cer");
+    let client = client(
+        &url,
+        X509Hint::ContentType("application/x-pem-file".to_string()),
+        body,
+        None,
+    );
+
+    // not a PEM body, buffered to fall back to the other formats
+    let certificates = client.get_stream(&url).collect::<Vec<_>>().await;
+    assert_eq!(1, certificates.len());
+    assert_eq!(&body[..], certificates[0].as_ref().unwrap().as_ref());
+}
+
+#[tokio::test]
+async fn test_stream_http() {
+    let server = HttpServer::start(|request| match request.path.as_str() {
+        "/redirect" => HttpResponse::new(302, b"").with_header("Location", "/ca.pem"),
+        "/ca.pem" => HttpResponse::new(200, format!("{}{}", BLOCK_1, BLOCK_2).as_bytes())
+            .with_header("Content-Type", "application/x-pem-file"),
+        _ => HttpResponse::new(404, b""),
+    })
+    .await;
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration::default());
+
+    let certificates = client
+        .get_stream(&server.url("/redirect"))
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(2, certificates.len());
+    assert!(certificates.iter().all(Result::is_ok));
+
+    let certificates = client
+        .get_stream(&server.url("/missing"))
+        .collect::<Vec<_>>()
+        .await;
+    assert!(matches!(
+        certificates.as_slice(),
+        [Err(X509ClientError::HttpStatus { status: 404, .. })]
+    ));
+
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
+        schemes: X509SchemePolicy::https(),
+        ..Default::default()
+    });
+    let certificates = client
+        .get_stream(&server.url("/ca.pem"))
+        .collect::<Vec<_>>()
+        .await;
+    assert!(matches!(
+        certificates.as_slice(),
+        [Err(X509ClientError::SchemeNotPermitted(_))]
+    ));
+}
@@ -0,0 +1,107 @@
+use crate::api::{X509Hint, X509Payload};
+use crate::provided::debug::DebugX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::{
+    X509Client, X509ClientConfiguration, X509ClientError, X509RequestOptions,
+    X509RetryConfiguration,
+};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use url::Url;
+
+fn url() -> Url {
+    Url::parse("http://localhost/ca.cer").unwrap()
+}
+
+fn transport(delay: Duration) -> MockX509Transport {
+    MockX509Transport::new()
+        .with_payload(url(), X509Payload::new(X509Hint::None, vec![1, 2, 3]))
+        .with_delay(delay)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_read_timeout() {
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            read_timeout: Some(Duration::from_millis(20)),
+            ..Default::default()
+        },
+        transport(Duration::from_millis(200)),
+    );
+
+    assert!(matches!(
+        client.get(&url()).await,
+        Err(X509ClientError::Timeout(timeout)) if timeout == Duration::from_millis(20)
+    ));
+
+    let options = X509RequestOptions {
+        read_timeout: Some(Duration::from_secs(5)),
+        ..Default::default()
+    };
+    assert!(client.get_all_with(&url(), &options).await.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_deadline() {
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            timeout: Some(Duration::from_millis(20)),
+            ..Default::default()
+        },
+        transport(Duration::from_millis(200)),
+    );
+
+    assert!(matches!(
+        client.get(&url()).await,
+        Err(X509ClientError::Timeout(timeout)) if timeout == Duration::from_millis(20)
+    ));
+
+    let options = X509RequestOptions {
+        timeout: Some(Duration::from_secs(5)),
+        ..Default::default()
+    };
+    assert!(client.get_all_with(&url(), &options).await.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_deadline_retries() {
+    let transport = transport(Duration::from_millis(200));
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration {
+            read_timeout: Some(Duration::from_millis(10)),
+            timeout: Some(Duration::from_millis(100)),
+            retry: Some(X509RetryConfiguration {
+                attempts: 100,
+                backoff: Duration::from_millis(10),
+                jitter: false,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        transport.clone(),
+    );
+
+    assert!(matches!(
+        client.get(&url()).await,
+        Err(X509ClientError::Timeout(timeout)) if timeout == Duration::from_millis(100)
+    ));
+    assert!(transport.requests().len() > 1);
+    assert!(transport.requests().len() < 100);
+}
+
+#[tokio::test]
+async fn test_http_connect_timeout() {
+    // accepts connections, never responds
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!("http://{}/ca.cer", listener.local_addr().unwrap())).unwrap();
+
+    let client = X509Client::<DebugX509Iterator>::new(X509ClientConfiguration {
+        connect_timeout: Some(Duration::from_millis(50)),
+        ..Default::default()
+    });
+
+    assert!(matches!(
+        client.get(&url).await,
+        Err(X509ClientError::Timeout(_))
+    ));
+}
@@ -0,0 +1,163 @@
+use crate::api::{X509Transport, X509TransportOptions};
+use crate::provided::default::DefaultX509Iterator;
+use crate::provided::transport::TlsX509Transport;
+use crate::{
+    X509Client, X509ClientConfiguration, X509ClientError, X509EgressPolicy, X509SchemePolicy,
+};
+use openssl::asn1::Asn1Time;
+use openssl::bn::BigNum;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::rsa::Rsa;
+use openssl::ssl::{NameType, SslAcceptor, SslMethod};
+use openssl::x509::{X509Name, X509};
+use std::pin::Pin;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_openssl::SslStream;
+use url::Url;
+
+#[tokio::test]
+async fn test_tls() {
+    let (port, mut server_names) = tls_server().await;
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::default(),
+        limit: None,
+        ..Default::default()
+    });
+
+    let url = Url::parse(&format!("tls://localhost:{}", port)).unwrap();
+    assert_eq!(2, client.get_all(&url).await.unwrap().into_iter().len());
+    assert_eq!(
+        Some("localhost".to_string()),
+        server_names.recv().await.unwrap()
+    );
+
+    let url = Url::parse(&format!("tls://127.0.0.1:{}?sni=tls.ciph.xxx", port)).unwrap();
+    assert_eq!(2, client.get_all(&url).await.unwrap().into_iter().len());
+    assert_eq!(
+        Some("tls.ciph.xxx".to_string()),
+        server_names.recv().await.unwrap()
+    );
+
+    let url = Url::parse(&format!("tls://127.0.0.1:{}", port)).unwrap();
+    assert_eq!(2, client.get_all(&url).await.unwrap().into_iter().len());
+    assert_eq!(None, server_names.recv().await.unwrap());
+
+    let client = X509Client::<DefaultX509Iterator>::new(X509ClientConfiguration {
+        strict: true,
+        schemes: X509SchemePolicy::default(),
+        limit: Some(16),
+        ..Default::default()
+    });
+    assert!(client.get_all(&url).await.is_err());
+}
+
+#[tokio::test]
+async fn test_tls_egress() {
+    let (port, mut server_names) = tls_server().await;
+    let url = Url::parse(&format!("tls://localhost:{}", port)).unwrap();
+    let options = X509TransportOptions::default();
+
+    // connects to the checked addresses, the host name is only used for SNI
+    let transport = TlsX509Transport::new().with_egress(Some(X509EgressPolicy {
+        block_private: false,
+        ..Default::default()
+    }));
+    assert_eq!(
+        2,
+        transport
+            .fetch(&url, &options)
+            .await
+            .unwrap()
+            .documents
+            .len()
+    );
+    assert_eq!(
+        Some("localhost".to_string()),
+        server_names.recv().await.unwrap()
+    );
+
+    let transport = TlsX509Transport::new().with_egress(Some(X509EgressPolicy::default()));
+    assert!(matches!(
+        transport.fetch(&url, &options).await,
+        Err(X509ClientError::EgressDenied(_))
+    ));
+}
+
+/// Local TLS listener presenting a generated two certificate chain, reports the received server name
+pub(crate) async fn tls_server() -> (u16, mpsc::UnboundedReceiver<Option<String>>) {
+    let acceptor = acceptor();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let ssl = openssl::ssl::Ssl::new(acceptor.context()).unwrap();
+            let mut stream = SslStream::new(ssl, stream).unwrap();
+            if Pin::new(&mut stream).accept().await.is_ok() {
+                let server_name = stream
+                    .ssl()
+                    .servername(NameType::HOST_NAME)
+                    .map(str::to_string);
+                tx.send(server_name).unwrap();
+            }
+        }
+    });
+
+    (port, rx)
+}
+
+pub(crate) fn acceptor() -> SslAcceptor {
+    let ca_key = key();
+    let ca = certificate("x509-client test ca", &ca_key, None);
+    let key = key();
+    let leaf = certificate("localhost", &key, Some((&ca, &ca_key)));
+
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server()).unwrap();
+    acceptor.set_private_key(&key).unwrap();
+    acceptor.set_certificate(&leaf).unwrap();
+    acceptor.add_extra_chain_cert(ca).unwrap();
+    acceptor.build()
+}
+
+fn key() -> PKey<Private> {
+    PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap()
+}
+
+fn certificate(cn: &str, key: &PKey<Private>, issuer: Option<(&X509, &PKey<Private>)>) -> X509 {
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_text("CN", cn).unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder
+        .set_serial_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap())
+        .unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_pubkey(key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+
+    match issuer {
+        None => {
+            builder.set_issuer_name(&name).unwrap();
+            builder.sign(key, MessageDigest::sha256()).unwrap();
+        }
+        Some((issuer, issuer_key)) => {
+            builder.set_issuer_name(issuer.subject_name()).unwrap();
+            builder.sign(issuer_key, MessageDigest::sha256()).unwrap();
+        }
+    }
+
+    builder.build()
+}
@@ -1,7 +1,12 @@
+use crate::api::{X509Hint, X509Payload};
 use crate::parse::X509Type;
+use crate::provided::debug::DebugX509Iterator;
+use crate::provided::transport::MockX509Transport;
+use crate::{X509Client, X509ClientConfiguration, X509TypeConfiguration};
 use http::header::CONTENT_TYPE;
 use http::{HeaderMap, HeaderValue};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use url::Url;
 
 #[test]
 fn test_x509type() {
@@ -47,3 +52,161 @@ fn test_x509type() {
     header.insert(CONTENT_TYPE, HeaderValue::from_str("?").unwrap());
     assert_eq!(X509Type::Unknown, X509Type::from(&header));
 }
+
+#[test]
+fn test_x509type_media_type() {
+    for (content_type, expected) in [
+        ("application/x-x509-ca-cert", X509Type::Cer),
+        ("application/x-x509-user-cert", X509Type::Cer),
+        ("Application/PKIX-Cert", X509Type::Cer),
+        ("application/x-pkcs7-certificates", X509Type::Pkcs7),
+        (
+            "application/pkcs7-mime; smime-type=certs-only",
+            X509Type::Pkcs7,
+        ),
+        (
+            "application/pkcs7-mime;smime-type=\"signed-data\"",
+            X509Type::Pkcs7,
+        ),
+        ("application/x-pkcs7-mime; name=ca.p7c", X509Type::Pkcs7),
+        (
+            "application/pkcs7-mime; smime-type=enveloped-data",
+            X509Type::Unknown,
+        ),
+        (
+            "application/pem-certificate-chain; charset=utf-8",
+            X509Type::Pem,
+        ),
+        ("application/x-pem-file", X509Type::Pem),
+        ("application/pkix-crl", X509Type::Crl),
+        ("text/plain; charset=utf-8", X509Type::Unknown),
+        ("", X509Type::Unknown),
+    ] {
+        assert_eq!(expected, X509Type::from(content_type), "{}", content_type);
+    }
+}
+
+#[tokio::test]
+async fn test_x509type_strict() {
+    let url = Url::parse("http://localhost/ca").unwrap();
+
+    for (content_type, count) in [
+        ("application/x-x509-ca-cert", 1),
+        ("application/pkcs7-mime; smime-type=certs-only", 1),
+        ("application/pem-certificate-chain; charset=utf-8", 1),
+        ("application/pkix-crl", 0),
+    ] {
+        let client = X509Client::<DebugX509Iterator, _>::with_transport(
+            X509ClientConfiguration {
+                strict: true,
+                ..Default::default()
+            },
+            MockX509Transport::new().with_payload(
+                url.clone(),
+                X509Payload::new(
+                    X509Hint::ContentType(content_type.to_string()),
+                    vec![1, 2, 3],
+                ),
+            ),
+        );
+        let certificates = client.get_all(&url).await.unwrap();
+        assert_eq!(count, certificates.into_iter().count(), "{}", content_type);
+    }
+}
+
+#[tokio::test]
+async fn test_x509type_relaxed_crl() {
+    let url = Url::parse("http://localhost/ca").unwrap();
+    let client = X509Client::<DebugX509Iterator, _>::with_transport(
+        X509ClientConfiguration::default(),
+        MockX509Transport::new().with_payload(
+            url.clone(),
+            X509Payload::new(
+                X509Hint::ContentType("application/pkix-crl".to_string()),
+                vec![1, 2, 3],
+            ),
+        ),
+    );
+
+    // the certificate of a mislabelled payload is deserialized rather than an empty CRL
+    let response = client
+        .get_response(&url, &Default::default())
+        .await
+        .unwrap();
+    assert_eq!(X509Type::Cer, response.formats[0].format);
+    assert_eq!(1, response.certificates.into_iter().count());
+}
+
+#[test]
+fn test_x509type_configuration() {
+    let default = X509TypeConfiguration::default();
+    assert_eq!(
+        X509Type::Pkcs7,
+        default.media_type("application/pkcs7-mime; smime-type=certs-only")
+    );
+    assert_eq!(
+        X509Type::Unknown,
+        default.media_type("application/octet-stream")
+    );
+    assert_eq!(X509Type::Pem, default.extension(Path::new("ca.PEM")));
+    assert_eq!(X509Type::Unknown, default.extension(Path::new("ca.crt")));
+
+    let mut types = X509TypeConfiguration::default();
+    types
+        .media_types
+        .insert("application/octet-stream".to_string(), X509Type::Cer);
+    types
+        .media_types
+        .insert("application/x-pem-file".to_string(), X509Type::Cer);
+    types.media_types.remove("application/pkix-cert");
+    types.extensions.insert("crt".to_string(), X509Type::Cer);
+    types.extensions.remove("pem");
+
+    assert_eq!(
+        X509Type::Cer,
+        types.media_type("Application/Octet-Stream; name=ca.crt")
+    );
+    assert_eq!(X509Type::Cer, types.media_type("application/x-pem-file"));
+    assert_eq!(X509Type::Unknown, types.media_type("application/pkix-cert"));
+    assert_eq!(X509Type::Cer, types.extension(Path::new("ca.crt")));
+    assert_eq!(X509Type::Unknown, types.extension(Path::new("ca.pem")));
+    assert_eq!(
+        X509Type::Cer,
+        types.hint(&X509Hint::Path(PathBuf::from("ca.crt")))
+    );
+    assert_eq!(X509Type::Pem, types.hint(&X509Hint::Type(X509Type::Pem)));
+}
+
+#[tokio::test]
+async fn test_x509type_configured_strict() {
+    let url = Url::parse("http://localhost/ca").unwrap();
+    let mut types = X509TypeConfiguration::default();
+    types
+        .media_types
+        .insert("application/octet-stream".to_string(), X509Type::Cer);
+    types.extensions.insert("der".to_string(), X509Type::Cer);
+
+    for hint in [
+        X509Hint::ContentType("application/octet-stream".to_string()),
+        X509Hint::Path(PathBuf::from("ca.der")),
+    ] {
+        let payload = X509Payload::new(hint.clone(), vec![1, 2, 3]);
+        let client = |types: X509TypeConfiguration| {
+            X509Client::<DebugX509Iterator, _>::with_transport(
+                X509ClientConfiguration {
+                    strict: true,
+                    types,
+                    ..Default::default()
+                },
+                MockX509Transport::new().with_payload(url.clone(), payload.clone()),
+            )
+        };
+
+        assert!(client(Default::default()).get_all(&url).await.is_err());
+        assert!(
+            client(types.clone()).get_all(&url).await.is_ok(),
+            "{:?}",
+            hint
+        );
+    }
+}
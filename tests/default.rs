@@ -5,6 +5,8 @@ use url::Url;
 use x509_client::provided::default::DefaultX509Iterator;
 use x509_client::X509Client;
 use x509_client::X509ClientConfiguration;
+use x509_client::X509ClientError;
+use x509_client::X509SchemePolicy;
 
 mod reqwest;
 
@@ -12,15 +14,15 @@ mod reqwest;
 async fn limit() {
     let limit_config = X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: Some(600),
-        http_client: None,
+        ..Default::default()
     };
     let no_limit_config = X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        http_client: None,
+        ..Default::default()
     };
 
     let url_small = Url::parse(
@@ -40,23 +42,26 @@ async fn limit() {
     assert!(client.get_all(&url_large).await.is_ok());
 
     let client = build_client(limit_config).await.unwrap();
-    assert!(client.get_all(&url_large).await.is_err());
+    assert!(matches!(
+        client.get_all(&url_large).await,
+        Err(X509ClientError::LimitExceeded { limit: 600, .. })
+    ));
 }
 
 #[tokio::test]
 async fn files() {
     let file_config = X509ClientConfiguration {
         strict: true,
-        files: true,
+        schemes: X509SchemePolicy::Any,
         limit: None,
-        http_client: None,
+        ..Default::default()
     };
 
     let no_file_config = X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        http_client: None,
+        ..Default::default()
     };
 
     let file = Path::new(env!("CARGO_TARGET_TMPDIR"))
@@ -74,9 +79,9 @@ async fn files() {
 async fn get_cer() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -100,9 +105,9 @@ async fn get_cer() {
 async fn load_cer() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: true,
+        schemes: X509SchemePolicy::Any,
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -124,9 +129,9 @@ async fn load_cer() {
 async fn get_pkcs7() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -150,9 +155,9 @@ async fn get_pkcs7() {
 async fn load_pkcs7() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: true,
+        schemes: X509SchemePolicy::Any,
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -174,9 +179,9 @@ async fn load_pkcs7() {
 async fn get_pem() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -200,9 +205,9 @@ async fn get_pem() {
 async fn load_pem() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: true,
+        schemes: X509SchemePolicy::Any,
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -224,9 +229,9 @@ async fn load_pem() {
 async fn get_pemstack() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -250,9 +255,9 @@ async fn get_pemstack() {
 async fn load_pemstack() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: true,
+        schemes: X509SchemePolicy::Any,
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
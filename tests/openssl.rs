@@ -7,6 +7,7 @@ use url::Url;
 use x509_client::provided::openssl::OpenSSLX509Iterator;
 use x509_client::X509Client;
 use x509_client::X509ClientConfiguration;
+use x509_client::X509SchemePolicy;
 
 mod reqwest;
 
@@ -14,9 +15,9 @@ mod reqwest;
 async fn get_cer() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -40,9 +41,9 @@ async fn get_cer() {
 async fn load_cer() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: true,
+        schemes: X509SchemePolicy::Any,
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -63,9 +64,9 @@ async fn load_cer() {
 async fn get_pkcs7() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -88,9 +89,9 @@ async fn get_pkcs7() {
 async fn load_pkcs7() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: true,
+        schemes: X509SchemePolicy::Any,
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -112,9 +113,9 @@ async fn load_pkcs7() {
 async fn get_pem() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -138,9 +139,9 @@ async fn get_pem() {
 async fn load_pem() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: true,
+        schemes: X509SchemePolicy::Any,
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -161,9 +162,9 @@ async fn load_pem() {
 async fn get_pemstack() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: false,
+        schemes: X509SchemePolicy::default(),
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();
@@ -187,9 +188,9 @@ async fn get_pemstack() {
 async fn load_pemstack() {
     let client = build_client(X509ClientConfiguration {
         strict: true,
-        files: true,
+        schemes: X509SchemePolicy::Any,
         limit: None,
-        http_client: None,
+        ..Default::default()
     })
     .await
     .unwrap();